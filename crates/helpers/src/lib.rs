@@ -37,3 +37,15 @@ pub fn provider_trait_name() -> proc_macro2::TokenStream {
         Provider
     }
 }
+
+pub fn async_provider_trait_name() -> proc_macro2::TokenStream {
+    quote! {
+        AsyncProvider
+    }
+}
+
+pub fn recorded_resource_name() -> proc_macro2::TokenStream {
+    quote! {
+        RecordedResource
+    }
+}
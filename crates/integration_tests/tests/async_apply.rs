@@ -0,0 +1,324 @@
+#[cfg(test)]
+mod test {
+
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use provider_macro::provider;
+    use runtime::{
+        serde_json::json, Cache, Context, Fault, FaultInjector, FaultKind, Layer, Layered, Metrics,
+        MockProvider, Next, Operation, ProviderError, Recorder, RecorderConfig, ResourceStatus,
+        Values,
+    };
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Subnet {
+            cidr: String,
+            #[output]
+            id: String,
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Router {
+            subnets: Vec<String>,
+        }
+    }
+
+    /// Waits on every operation without blocking the thread, and tracks how many ran at once.
+    #[derive(Default)]
+    struct Slow {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+        created: Mutex<Vec<String>>,
+    }
+
+    impl Slow {
+        async fn call(&self, ctx: &Context) {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            runtime::sleep(Duration::from_millis(30)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            if ctx.operation == Operation::Create {
+                self.created.lock().unwrap().push(ctx.resource.clone());
+            }
+        }
+    }
+
+    impl cloud::prelude::AsyncProvider for Slow {
+        type Config = ();
+
+        fn configure(_config: ()) -> Result<Self, runtime::ConfigError> {
+            Ok(Self::default())
+        }
+
+        async fn create<R: cloud::prelude::Resource + Send>(
+            &self,
+            ctx: &Context,
+            resource: &mut R,
+        ) -> Result<(), ProviderError> {
+            self.call(ctx).await;
+            resource.set_outputs();
+            Ok(())
+        }
+
+        async fn delete<R: cloud::prelude::Resource + Sync>(
+            &self,
+            ctx: &Context,
+            _resource: &R,
+        ) -> Result<(), ProviderError> {
+            self.call(ctx).await;
+            Ok(())
+        }
+    }
+
+    /// Never finishes creating anything.
+    struct Stuck;
+
+    impl cloud::prelude::AsyncProvider for Stuck {
+        type Config = ();
+
+        fn configure(_config: ()) -> Result<Self, runtime::ConfigError> {
+            Ok(Self)
+        }
+
+        async fn create<R: cloud::prelude::Resource + Send>(
+            &self,
+            _ctx: &Context,
+            _resource: &mut R,
+        ) -> Result<(), ProviderError> {
+            std::future::pending().await
+        }
+    }
+
+    /// A layer written for blocking providers only.
+    struct Blocking;
+
+    impl Layer for Blocking {
+        fn call(
+            &self,
+            _ctx: &Context,
+            _inputs: &Values,
+            next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            next()
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_independent_resources_overlap_on_one_thread() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = router)]
+            Router {
+                subnets: vec![a.get_id(), b.get_id(), c.get_id()],
+            }
+
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+
+            #[resource(name = c)]
+            Subnet {
+                cidr: String::from("10.0.3.0/24"),
+            }
+        }
+
+        let state = State::from_configured(Slow::default());
+        let mut state_file = runtime::StateFile::new();
+
+        let report = state.apply_async(&mut state_file).await.unwrap();
+        assert_eq!(report.resources.len(), 4);
+        assert_eq!(state.provider().peak.load(Ordering::SeqCst), 3);
+        assert_eq!(state.provider().created.lock().unwrap()[3], "router");
+        assert!(!state_file.get("router").unwrap().inputs.is_empty());
+
+        state.destroy_async(&mut state_file).await.unwrap();
+        assert!(state_file.resources().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_async_can_be_spawned() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new());
+        let state_file = tokio::spawn(async move {
+            let mut state_file = runtime::StateFile::new();
+            state.apply_async(&mut state_file).await.unwrap();
+            state_file
+        })
+        .await
+        .unwrap();
+
+        assert!(state_file.get("a").is_some());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_pending_async_call_is_given_up_at_the_deadline() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a, timeouts(create = "20ms"))]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        let state = State::from_configured(Stuck);
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply_async(&mut state_file).await.unwrap_err();
+        assert!(err.errors()[0].is_timeout());
+        assert_eq!(
+            state_file.get("a").unwrap().status,
+            ResourceStatus::Uncertain
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_layers_run_around_async_operations() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+        }
+
+        let metrics = Metrics::new();
+        let state = State::from_configured(
+            Layered::new(Slow::default())
+                .layer(metrics.clone())
+                .layer(Cache::new()),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply_async(&mut state_file).await.unwrap();
+        assert_eq!(metrics.get("Subnet", Operation::Create).calls, 2);
+        assert_eq!(state.provider().inner().peak.load(Ordering::SeqCst), 2);
+        assert!(!state_file.get("a").unwrap().outputs.is_empty());
+
+        state.destroy_async(&mut state_file).await.unwrap();
+        assert_eq!(metrics.get("Subnet", Operation::Delete).calls, 2);
+        assert!(state_file.resources().is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_blocking_only_layer_fails_async_operations() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        let state = State::from_configured(Layered::new(MockProvider::new()).layer(Blocking));
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply_async(&mut state_file).await.unwrap_err();
+        assert!(err.errors()[0]
+            .to_string()
+            .contains("does not support async providers"));
+        assert!(state.provider().inner().calls().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_injected_faults_are_retried_async() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        let provider = FaultInjector::new(MockProvider::new()).with_fault(
+            Fault::new(FaultKind::Error(ProviderError::retryable("throttled")))
+                .on(Operation::Create),
+        );
+        let state = State::from_configured(provider);
+        let state_file = tokio::spawn(async move {
+            let mut state_file = runtime::StateFile::new();
+            let report = state.apply_async(&mut state_file).await.unwrap();
+            assert_eq!(report.get("a").unwrap().attempts, 2);
+            assert_eq!(state.provider().injected().len(), 1);
+            state_file
+        })
+        .await
+        .unwrap();
+
+        assert!(state_file.get("a").is_some());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_replays_a_session_recorded_async() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        let fixture =
+            std::env::temp_dir().join(format!("rust_iac_record_async_{}.json", std::process::id()));
+        let mock = MockProvider::new().with_outputs(
+            "Subnet",
+            Values::from([(String::from("id"), json!("subnet-1"))]),
+        );
+
+        let state = State::from_configured(Recorder::record(mock, &fixture));
+        let mut state_file = runtime::StateFile::new();
+        state.apply_async(&mut state_file).await.unwrap();
+        state.destroy_async(&mut state_file).await.unwrap();
+        state.provider().finish().unwrap();
+
+        let state = State::<Recorder<MockProvider>>::configure(RecorderConfig::Replay {
+            path: fixture.clone(),
+        })
+        .unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply_async(&mut state_file).await.unwrap();
+        assert_eq!(
+            state_file.get("a").unwrap().outputs.get("id"),
+            Some(&json!("subnet-1"))
+        );
+        state.destroy_async(&mut state_file).await.unwrap();
+        state.provider().finish().unwrap();
+
+        std::fs::remove_file(&fixture).unwrap();
+    }
+}
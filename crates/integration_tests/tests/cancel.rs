@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test {
+
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use provider_macro::provider;
+    use runtime::{
+        CancellationToken, Context, Layer, Layered, MockProvider, Next, ProviderError,
+        ResourceStatus, Values,
+    };
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Disk {
+            size: u32,
+        }
+    }
+
+    /// Cancels the run while the named resource is being created, like a Ctrl-C would.
+    struct CancelDuring {
+        resource: &'static str,
+        cancellation: CancellationToken,
+    }
+
+    impl Layer for CancelDuring {
+        fn call(
+            &self,
+            ctx: &Context,
+            _inputs: &Values,
+            next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            if ctx.resource == self.resource {
+                self.cancellation.cancel();
+            }
+            next()
+        }
+    }
+
+    /// Hangs every call until its deadline or the end of the grace period, then gives up as a
+    /// provider should.
+    struct Hang;
+
+    impl Layer for Hang {
+        fn call(
+            &self,
+            ctx: &Context,
+            _inputs: &Values,
+            _next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            thread::sleep(ctx.remaining().expect("the call is given a deadline"));
+            Err(ctx.timed_out())
+        }
+    }
+
+    #[test]
+    fn test_cancel_starts_no_new_operations() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Disk {
+                size: 10,
+            }
+
+            #[resource(name = b)]
+            Disk {
+                size: 20,
+            }
+
+            #[resource(name = c)]
+            Disk {
+                size: 30,
+            }
+        }
+
+        let cancellation = CancellationToken::new();
+        let provider = Layered::new(MockProvider::new()).layer(CancelDuring {
+            resource: "a",
+            cancellation: cancellation.clone(),
+        });
+        let state = State::from_configured(provider)
+            .with_parallelism(1)
+            .with_cancellation(cancellation);
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(err.is_interrupted());
+        assert_eq!(
+            err.to_string(),
+            "Interrupted: 1 succeeded, 0 failed, 2 not applied"
+        );
+
+        let report = err.report().unwrap();
+        assert!(report.get("a").is_some());
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(
+            report.skipped[0].reason,
+            "not started, the run was interrupted"
+        );
+        assert_eq!(state.provider().inner().calls().len(), 1);
+        assert_eq!(state_file.resources().len(), 1);
+        assert!(state_file.get("a").is_some());
+    }
+
+    #[test]
+    fn test_operation_outlasting_grace_period_is_uncertain() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Disk {
+                size: 10,
+            }
+        }
+
+        let cancellation = CancellationToken::new().with_grace_period(Duration::from_millis(20));
+        let provider = Layered::new(MockProvider::new())
+            .layer(CancelDuring {
+                resource: "a",
+                cancellation: cancellation.clone(),
+            })
+            .layer(Hang);
+        let state = State::from_configured(provider).with_cancellation(cancellation);
+        let mut state_file = runtime::StateFile::new();
+
+        let start = Instant::now();
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err.is_interrupted());
+        assert!(err.errors()[0].is_timeout());
+        assert_eq!(
+            state_file.get("a").unwrap().status,
+            ResourceStatus::Uncertain
+        );
+    }
+
+    #[test]
+    fn test_cancel_during_the_last_operation_is_interrupted() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Disk {
+                size: 10,
+            }
+        }
+
+        let cancellation = CancellationToken::new();
+        let provider = Layered::new(MockProvider::new()).layer(CancelDuring {
+            resource: "a",
+            cancellation: cancellation.clone(),
+        });
+        let state = State::from_configured(provider).with_cancellation(cancellation);
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(err.is_interrupted());
+        assert_eq!(
+            err.to_string(),
+            "Interrupted: 1 succeeded, 0 failed, 0 not applied"
+        );
+        assert!(state_file.get("a").is_some());
+    }
+}
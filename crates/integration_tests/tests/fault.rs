@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{Fault, FaultInjector, MockProvider, Operation};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Disk {
+            size: u32,
+            #[output]
+            id: String,
+        }
+    }
+
+    #[test]
+    fn test_failed_create_keeps_earlier_resources() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = boot)]
+            Disk {
+                size: 10,
+            }
+
+            #[resource(name = data)]
+            Disk {
+                size: 100,
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(MockProvider::new()).with_fault(
+                Fault::error("quota exceeded")
+                    .on(Operation::Create)
+                    .resource("data"),
+            ),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert_eq!(err.to_string(), "Failed to create 'data': quota exceeded");
+        assert!(state_file.get("boot").is_some());
+        assert!(state_file.get("data").is_none());
+
+        state.apply(&mut state_file).unwrap();
+        assert!(state_file.get("data").is_some());
+        assert_eq!(state.provider().injected().len(), 1);
+    }
+
+    #[test]
+    fn test_failure_after_side_effect_leaves_untracked_resource() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = boot)]
+            Disk {
+                size: 10,
+            }
+        }
+
+        let state = State::from_configured(FaultInjector::new(MockProvider::new()));
+        state
+            .provider()
+            .add_fault(Fault::after_side_effect("connection reset").on(Operation::Create));
+        let mut state_file = runtime::StateFile::new();
+
+        assert!(state.apply(&mut state_file).is_err());
+        assert!(state_file.get("boot").is_none());
+        assert_eq!(
+            state.provider().inner().resources(),
+            vec![String::from("boot")]
+        );
+    }
+}
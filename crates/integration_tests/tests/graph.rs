@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::MockProvider;
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Subnet {
+            cidr: String,
+            #[output]
+            id: String,
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Router {
+            subnets: Vec<String>,
+        }
+    }
+
+    #[test]
+    fn test_resource_graph_renders_dependencies_and_planned_actions() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = router)]
+            Router {
+                subnets: vec![a.get_id(), b.get_id()],
+            }
+
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+        }
+
+        let graph = State::<MockProvider>::resource_graph();
+        let router = graph.get("router").unwrap();
+        assert_eq!(router.resource_type, "Router");
+        assert_eq!(router.depends_on, vec!["a", "b"]);
+        assert!(graph.get("a").unwrap().depends_on.is_empty());
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("    \"router\" [label=\"router\\nRouter\"];\n"));
+        assert!(dot.contains("    \"a\" -> \"router\";\n"));
+        assert!(dot.contains("    \"b\" -> \"router\";\n"));
+        assert!(!dot.contains("fillcolor"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("    a[\"a<br/>Subnet\"]\n"));
+        assert!(mermaid.contains("    a --> router\n"));
+
+        let state = State::from_configured(MockProvider::new());
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+        state_file.remove("b");
+
+        let graph = graph.with_plan(&state.plan(&state_file).unwrap());
+        assert_eq!(
+            graph.get("b").unwrap().action,
+            Some(runtime::Action::Create)
+        );
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("    style b fill:#b7e4c7\n"));
+        assert!(!mermaid.contains("style a "));
+    }
+
+    #[test]
+    fn test_state_exposes_resources_dependencies_and_execution_order() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = resource_a)]
+            Router {
+                subnets: vec![resource_b.get_id()],
+            }
+
+            #[resource(name = resource_b)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+        }
+
+        const _: () = assert!(EXECUTION_ORDER.len() == 2);
+        assert_eq!(EXECUTION_ORDER, ["resource_b", "resource_a"]);
+        assert_eq!(RESOURCE_NAMES, ["resource_a", "resource_b"]);
+        assert_eq!(
+            RESOURCE_TYPES,
+            [("resource_a", "Router"), ("resource_b", "Subnet")]
+        );
+        assert_eq!(DEPENDENCIES[0], ("resource_a", &["resource_b"][..]));
+
+        type Cloud = State<MockProvider>;
+        assert_eq!(Cloud::resource_names(), RESOURCE_NAMES);
+        assert_eq!(Cloud::execution_order(), EXECUTION_ORDER);
+        assert_eq!(Cloud::resource_type("resource_b"), Some("Subnet"));
+        assert_eq!(Cloud::resource_type("missing"), None);
+        assert_eq!(Cloud::dependencies("resource_a"), Some(&["resource_b"][..]));
+        assert_eq!(Cloud::dependencies("resource_b"), Some(&[][..]));
+    }
+}
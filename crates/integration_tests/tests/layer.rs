@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod test {
+
+    use std::sync::{Arc, Mutex};
+
+    use provider_macro::provider;
+    use runtime::{
+        Cache, Context, Fault, FaultInjector, Layer, Layered, Logging, Metrics, MockProvider, Next,
+        Operation, ProviderError, Values,
+    };
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Queue {
+            name: String,
+            #[output]
+            url: String,
+        }
+    }
+
+    struct Retry(usize);
+
+    impl Layer for Retry {
+        fn call(
+            &self,
+            _ctx: &Context,
+            _inputs: &Values,
+            next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            let mut result = next();
+            for _ in 1..self.0 {
+                if result.is_ok() {
+                    break;
+                }
+                result = next();
+            }
+            result
+        }
+    }
+
+    #[test]
+    fn test_layers_see_every_operation() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = jobs)]
+            Queue {
+                name: String::from("jobs"),
+            }
+        }
+
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let log = lines.clone();
+        let metrics = Metrics::new();
+        let state = State::from_configured(
+            Layered::new(MockProvider::new())
+                .layer(Logging::to(move |line| {
+                    log.lock().unwrap().push(line.to_string())
+                }))
+                .layer(metrics.clone())
+                .layer(Cache::new()),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+        state.apply(&mut state_file).unwrap();
+        state.destroy(&mut state_file).unwrap();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("create 'jobs' (Queue) ok"));
+        assert!(lines[1].starts_with("read 'jobs' (Queue) ok"));
+        assert!(lines[2].starts_with("delete 'jobs' (Queue) ok"));
+
+        assert_eq!(metrics.get("Queue", Operation::Create).calls, 1);
+        assert_eq!(metrics.total_calls(), 3);
+
+        state
+            .provider()
+            .inner()
+            .assert_operations(&[(Operation::Create, "jobs"), (Operation::Delete, "jobs")]);
+    }
+
+    #[test]
+    fn test_layers_can_retry_operations() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = jobs)]
+            Queue {
+                name: String::from("jobs"),
+            }
+        }
+
+        let state = State::from_configured(
+            Layered::new(
+                FaultInjector::new(MockProvider::new())
+                    .with_fault(Fault::error("throttled").on(Operation::Create).times(2)),
+            )
+            .layer(Retry(3)),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+
+        assert_eq!(state.provider().inner().injected().len(), 2);
+        assert!(state_file.get("jobs").is_some());
+    }
+}
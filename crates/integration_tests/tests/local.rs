@@ -0,0 +1,200 @@
+#[cfg(test)]
+mod test {
+
+    use std::{fs, path::PathBuf};
+
+    use providers::local::prelude::*;
+    use runtime::serde_json::json;
+    use state_macro::state;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("rust_iac_local_{}", name));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn test_local_provider_lifecycle() {
+        state! {
+            #[resource(name = config_dir)]
+            Directory {
+                path: String::from("etc"),
+            }
+
+            #[resource(name = config_file)]
+            File {
+                path: format!("{}/app.conf", config_dir.get_absolute_path()),
+                content: String::from("hello"),
+                mode: Some(0o600),
+            }
+
+            #[resource(name = config_link)]
+            Symlink {
+                path: String::from("app.conf"),
+                target: config_file.get_absolute_path(),
+            }
+        }
+
+        let root = temp_root("lifecycle");
+        let state = State::<LocalProvider>::configure(Config {
+            root: Some(root.to_string_lossy().into_owned()),
+        })
+        .unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+
+        let file = root.join("etc/app.conf");
+        let record = state_file.get("config_file").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello");
+        assert_eq!(
+            record.outputs.get("absolute_path"),
+            Some(&json!(file.to_str().unwrap()))
+        );
+        assert_eq!(
+            record.outputs.get("content_hash"),
+            Some(&json!(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            ))
+        );
+        assert_eq!(fs::read_link(root.join("app.conf")).unwrap(), file);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::write(&file, "changed").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(
+            report.get("config_file").unwrap().action,
+            runtime::Action::NoOp
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello");
+        assert_eq!(
+            state_file
+                .get("config_file")
+                .unwrap()
+                .outputs
+                .get("content_hash"),
+            Some(&json!(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            ))
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        state.destroy(&mut state_file).unwrap();
+        assert!(state_file.resources().is_empty());
+        assert!(!root.join("etc").exists());
+        assert!(fs::symlink_metadata(root.join("app.conf")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_local_provider_moves_file_on_update() {
+        state! {
+            #[resource(name = file)]
+            File {
+                path: String::from("new.txt"),
+                content: String::from("content"),
+            }
+        }
+
+        let root = temp_root("update");
+        let state = State::<LocalProvider>::configure(Config {
+            root: Some(root.to_string_lossy().into_owned()),
+        })
+        .unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("old.txt"), "content").unwrap();
+        let mut record = runtime::ResourceRecord::new(
+            "file",
+            "File",
+            runtime::ProviderInfo::new(LocalProvider::NAME, LocalProvider::VERSION),
+        );
+        record.inputs.insert(String::from("path"), json!("old.txt"));
+        record.outputs.insert(
+            String::from("absolute_path"),
+            json!(root.join("old.txt").to_str().unwrap()),
+        );
+        state_file.record(record);
+
+        let report = state.apply(&mut state_file).unwrap();
+
+        assert_eq!(report.get("file").unwrap().action, runtime::Action::Update);
+        assert!(!root.join("old.txt").exists());
+        assert_eq!(fs::read_to_string(root.join("new.txt")).unwrap(), "content");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_local_provider_resets_a_removed_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        state! {
+            #[resource(name = file)]
+            File {
+                path: String::from("secret.txt"),
+                content: String::from("content"),
+            }
+        }
+
+        let root = temp_root("mode");
+        let state = State::<LocalProvider>::configure(Config {
+            root: Some(root.to_string_lossy().into_owned()),
+        })
+        .unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        let file = root.join("secret.txt");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("default.txt"), "").unwrap();
+        fs::write(&file, "content").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+        let mut record = runtime::ResourceRecord::new(
+            "file",
+            "File",
+            runtime::ProviderInfo::new(LocalProvider::NAME, LocalProvider::VERSION),
+        );
+        record
+            .inputs
+            .insert(String::from("path"), json!("secret.txt"));
+        record
+            .inputs
+            .insert(String::from("content"), json!("content"));
+        record.inputs.insert(String::from("mode"), json!(0o600));
+        record
+            .outputs
+            .insert(String::from("absolute_path"), json!(file.to_str().unwrap()));
+        state_file.record(record);
+
+        let report = state.apply(&mut state_file).unwrap();
+
+        let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(report.get("file").unwrap().action, runtime::Action::Update);
+        assert_eq!(mode(file), mode(root.join("default.txt")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
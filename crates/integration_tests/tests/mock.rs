@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{serde_json::json, MockProvider, Operation, Values};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Network {
+            cidr: String,
+            #[output]
+            id: String,
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Server {
+            network_id: String,
+            #[input(default = 1)]
+            count: u32,
+            #[output]
+            id: String,
+        }
+    }
+
+    #[test]
+    fn test_mock_provider_records_operations() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = server)]
+            Server {
+                network_id: network.get_id(),
+            }
+
+            #[resource(name = network)]
+            Network {
+                cidr: String::from("10.0.0.0/16"),
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new().with_outputs(
+            "Network",
+            Values::from([(String::from("id"), json!("net-1"))]),
+        ));
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+        state.apply(&mut state_file).unwrap();
+        state.destroy(&mut state_file).unwrap();
+
+        let mock = state.provider();
+        mock.assert_operations(&[
+            (Operation::Create, "network"),
+            (Operation::Create, "server"),
+            (Operation::Read, "network"),
+            (Operation::Read, "server"),
+            (Operation::Delete, "server"),
+            (Operation::Delete, "network"),
+        ]);
+        assert_eq!(
+            mock.calls()[1].inputs,
+            Values::from([
+                (String::from("network_id"), json!("net-1")),
+                (String::from("count"), json!(1)),
+            ])
+        );
+        assert!(mock.resources().is_empty());
+    }
+
+    #[test]
+    fn test_mock_provider_fails_for_missing_resource() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = network)]
+            Network {
+                cidr: String::from("10.0.0.0/16"),
+            }
+        }
+
+        let state = State::<MockProvider>::configure(()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+
+        let state = State::from_configured(MockProvider::new());
+        let err = state.apply(&mut state_file).err().unwrap();
+
+        assert!(err.to_string().contains("does not exist"));
+        state.provider().assert_called(Operation::Read, "network");
+    }
+}
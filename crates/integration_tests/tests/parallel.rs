@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod test {
+
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use provider_macro::provider;
+    use runtime::{Context, Layer, Layered, MockProvider, Next, Operation, ProviderError, Values};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Subnet {
+            cidr: String,
+            #[output]
+            id: String,
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Router {
+            subnets: Vec<String>,
+        }
+    }
+
+    /// Holds every operation for a while and tracks how many ran at once.
+    #[derive(Clone, Default)]
+    struct Concurrency {
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl Layer for Concurrency {
+        fn call(
+            &self,
+            _ctx: &Context,
+            _inputs: &Values,
+            next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            next()
+        }
+    }
+
+    #[test]
+    fn test_independent_resources_run_concurrently() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = router)]
+            Router {
+                subnets: vec![a.get_id(), b.get_id(), c.get_id()],
+            }
+
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+
+            #[resource(name = c)]
+            Subnet {
+                cidr: String::from("10.0.3.0/24"),
+            }
+        }
+
+        let concurrency = Concurrency::default();
+        let state =
+            State::from_configured(Layered::new(MockProvider::new()).layer(concurrency.clone()))
+                .with_parallelism(2);
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+        assert_eq!(concurrency.peak.load(Ordering::SeqCst), 2);
+
+        let calls = state.provider().inner().calls();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[3].resource, "router");
+
+        state.destroy(&mut state_file).unwrap();
+        let calls = state.provider().inner().calls();
+        assert_eq!(calls[4].operation, Operation::Delete);
+        assert_eq!(calls[4].resource, "router");
+        assert!(state_file.resources().is_empty());
+    }
+
+    #[test]
+    fn test_parallelism_of_one_applies_sequentially() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+        }
+
+        let concurrency = Concurrency::default();
+        let state =
+            State::from_configured(Layered::new(MockProvider::new()).layer(concurrency.clone()))
+                .with_parallelism(1);
+
+        state.apply(&mut runtime::StateFile::new()).unwrap();
+        assert_eq!(concurrency.peak.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{Fault, FaultInjector, MockProvider, Operation};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Subnet {
+            cidr: String,
+            #[output]
+            id: String,
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Router {
+            subnets: Vec<String>,
+        }
+    }
+
+    #[test]
+    fn test_failure_skips_dependents_and_keeps_independent_resources() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = router)]
+            Router {
+                subnets: vec![a.get_id(), b.get_id()],
+            }
+
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = b)]
+            Subnet {
+                cidr: String::from("10.0.2.0/24"),
+            }
+
+            #[resource(name = c)]
+            Subnet {
+                cidr: String::from("10.0.3.0/24"),
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(MockProvider::new()).with_fault(
+                Fault::error("quota exceeded")
+                    .resource("a")
+                    .on(Operation::Create),
+            ),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert_eq!(err.to_string(), "Failed to create 'a': quota exceeded");
+
+        let report = err.report().unwrap();
+        assert!(!report.is_complete());
+        assert!(report.get("b").is_some() && report.get("c").is_some());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "a");
+        assert_eq!(
+            report.failed[0].reason,
+            "Failed to create 'a': quota exceeded"
+        );
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].name, "router");
+        assert_eq!(report.skipped[0].resource_type, "Router");
+        assert_eq!(report.skipped[0].reason, "waiting on 'a', which failed");
+
+        assert!(state_file.get("b").is_some() && state_file.get("c").is_some());
+        assert!(state_file.get("a").is_none() && state_file.get("router").is_none());
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert!(report.is_complete());
+        assert_eq!(state_file.resources().len(), 4);
+    }
+
+    #[test]
+    fn test_failed_delete_keeps_its_dependencies() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = router)]
+            Router {
+                subnets: vec![a.get_id()],
+            }
+
+            #[resource(name = a)]
+            Subnet {
+                cidr: String::from("10.0.1.0/24"),
+            }
+
+            #[resource(name = c)]
+            Subnet {
+                cidr: String::from("10.0.3.0/24"),
+            }
+        }
+
+        let state = State::from_configured(FaultInjector::new(MockProvider::new()));
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+
+        state.provider().add_fault(
+            Fault::error("in use")
+                .resource("router")
+                .on(Operation::Delete),
+        );
+        let err = state.destroy(&mut state_file).err().unwrap();
+
+        let report = err.report().unwrap();
+        assert_eq!(report.failed[0].name, "router");
+        assert_eq!(report.skipped[0].name, "a");
+        assert_eq!(
+            report.skipped[0].reason,
+            "waiting on 'router', which failed"
+        );
+        assert!(state_file.get("c").is_none());
+        assert!(state_file.get("a").is_some() && state_file.get("router").is_some());
+    }
+}
@@ -0,0 +1,400 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::serde_json::json;
+    use runtime::{
+        Action, ApplyError, Context, Layer, Layered, MockProvider, Next, Operation, Plan,
+        PlanError, ProviderError, Values,
+    };
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Database {
+            #[input(force_new)]
+            zone: String,
+            #[input(sensitive)]
+            password: String,
+            size: u32,
+            #[output]
+            id: String,
+        }
+    }
+
+    /// Changes a recorded input, as if the definition had changed since the last apply.
+    fn record_input(state_file: &mut runtime::StateFile, field: &str, value: runtime::Value) {
+        let mut record = state_file.get("db").unwrap().clone();
+        record.inputs.insert(field.to_string(), value);
+        state_file.record(record);
+    }
+
+    #[test]
+    fn test_plan_renders_changes_and_replaces_on_force_new_inputs() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                zone: String::from("eu-1"),
+                password: String::from("hunter2"),
+                size: 10,
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new());
+        let mut state_file = runtime::StateFile::new();
+
+        assert_eq!(
+            state.plan(&state_file).unwrap().to_string(),
+            r#"  # Database.db will be created
+  + Database.db {
+      + id       = (known after apply)
+      + password = (sensitive value)
+      + size     = 10
+      + zone     = "eu-1"
+    }
+
+Plan: 1 to add, 0 to change, 0 to destroy.
+"#
+        );
+        state.apply(&mut state_file).unwrap();
+
+        record_input(&mut state_file, "size", json!(5));
+        record_input(&mut state_file, "password", json!("secret"));
+        let rendered = state.plan(&state_file).unwrap().to_string();
+        assert!(rendered.contains("  ~ Database.db {\n"));
+        assert!(rendered.contains("      ~ size     = 5 -> 10\n"));
+        assert!(rendered.contains("      ~ password = (sensitive value)\n"));
+        assert!(!rendered.contains("zone"));
+        assert!(!rendered.contains("secret") && !rendered.contains("hunter2"));
+        assert!(rendered.ends_with("Plan: 0 to add, 1 to change, 0 to destroy.\n"));
+        state.apply(&mut state_file).unwrap();
+
+        record_input(&mut state_file, "zone", json!("us-1"));
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(plan.get("db").unwrap().action, Action::Replace);
+        let rendered = plan.to_string();
+        assert!(rendered.contains("-/+ Database.db {\n"));
+        assert!(rendered.contains("      ~ zone = \"us-1\" -> \"eu-1\" # forces replacement\n"));
+        assert!(rendered.ends_with("Plan: 1 to add, 0 to change, 1 to destroy.\n"));
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("db").unwrap().action, Action::Replace);
+
+        let calls = state.provider().calls();
+        let delete = &calls[calls.len() - 2];
+        assert_eq!(delete.operation, Operation::Delete);
+        assert_eq!(delete.inputs["zone"], json!("us-1"));
+        assert_eq!(calls[calls.len() - 1].operation, Operation::Create);
+        assert_eq!(calls[calls.len() - 1].inputs["zone"], json!("eu-1"));
+
+        assert_eq!(
+            state.plan(&state_file).unwrap().to_string(),
+            "No changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_exports_json_with_reasons_and_dependencies() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = primary)]
+            Database {
+                zone: String::from("eu-1"),
+                password: String::from("hunter2"),
+                size: 10,
+            }
+
+            #[resource(name = db)]
+            Database {
+                zone: primary.get_id(),
+                password: String::from("hunter2"),
+                size: 10,
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new());
+        let mut state_file = runtime::StateFile::new();
+
+        let plan = state.plan(&state_file).unwrap();
+        let db = plan.get("db").unwrap();
+        assert_eq!(db.address, "Database.db");
+        assert_eq!(db.reason, "not in state");
+        assert_eq!(db.depends_on, vec![String::from("primary")]);
+        assert!(plan.get("primary").unwrap().depends_on.is_empty());
+        let json = plan.to_json().unwrap();
+        assert!(!json.contains("hunter2"));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan.masked());
+
+        state.apply(&mut state_file).unwrap();
+        record_input(&mut state_file, "password", json!("secret"));
+        record_input(&mut state_file, "size", json!(5));
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(
+            plan.get("db").unwrap().reason,
+            "`password` and `size` changed"
+        );
+        assert_eq!(plan.get("primary").unwrap().reason, "up to date");
+
+        record_input(&mut state_file, "zone", json!("us-1"));
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(
+            plan.get("db").unwrap().reason,
+            "`zone` cannot be updated in place"
+        );
+
+        let loaded = Plan::from_json(&plan.to_json().unwrap()).unwrap();
+        assert_eq!(loaded, plan.masked());
+        assert_eq!(loaded.get("db").unwrap().before["zone"], json!("us-1"));
+    }
+
+    #[test]
+    fn test_resources_removed_from_the_definition_are_destroyed() {
+        use crate::test::cloud::prelude::*;
+
+        let mut state_file = runtime::StateFile::new();
+
+        {
+            state! {
+                #[resource(name = primary)]
+                Database {
+                    zone: String::from("eu-1"),
+                    password: String::from("hunter2"),
+                    size: 10,
+                }
+
+                #[resource(name = db)]
+                Database {
+                    zone: String::from("eu-1"),
+                    password: String::from("hunter2"),
+                    size: 10,
+                }
+            }
+
+            let state = State::from_configured(MockProvider::new());
+            state.apply(&mut state_file).unwrap();
+        }
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                zone: String::from("eu-1"),
+                password: String::from("hunter2"),
+                size: 10,
+            }
+        }
+
+        let provider = ["primary", "db"]
+            .iter()
+            .fold(MockProvider::new(), |provider, &name| {
+                let record = state_file.get(name).unwrap();
+                provider.with_resource(
+                    name,
+                    runtime::MockResource {
+                        resource_type: record.resource_type.clone(),
+                        inputs: record.inputs.clone(),
+                        outputs: record.outputs.clone(),
+                    },
+                )
+            });
+        let state = State::from_configured(provider);
+
+        let plan = state.plan(&state_file).unwrap();
+        let primary = plan.get("primary").unwrap();
+        assert_eq!(primary.action, Action::Delete);
+        assert_eq!(primary.reason, "not in configuration");
+        let rendered = plan.to_string();
+        assert!(rendered.contains("  # Database.primary will be destroyed\n"));
+        assert!(rendered.contains("      - password = (sensitive value) -> null\n"));
+        assert!(rendered.ends_with("Plan: 0 to add, 0 to change, 1 to destroy.\n"));
+
+        let report = state.apply_plan(&plan, &mut state_file).unwrap();
+        assert_eq!(report.get("primary").unwrap().action, Action::Delete);
+        state.provider().assert_called(Operation::Delete, "primary");
+        assert_eq!(state.provider().resources(), vec!["db"]);
+        assert!(state_file.get("primary").is_none());
+        assert_eq!(
+            state.plan(&state_file).unwrap().to_string(),
+            "No changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_plan_refuses_stale_plans() {
+        use crate::test::cloud::prelude::*;
+
+        let path = std::env::temp_dir().join("rust_iac_saved_plan.json");
+
+        {
+            state! {
+                #[resource(name = db)]
+                Database {
+                    zone: String::from("eu-1"),
+                    password: String::from("hunter2"),
+                    size: 10,
+                }
+            }
+
+            let state = State::from_configured(MockProvider::new());
+            let mut state_file = runtime::StateFile::new();
+            state.plan(&state_file).unwrap().save(&path).unwrap();
+
+            let plan = Plan::load(&path).unwrap();
+            assert!(plan.fingerprints.is_some());
+            let report = state.apply_plan(&plan, &mut state_file).unwrap();
+            assert_eq!(report.get("db").unwrap().action, Action::Create);
+
+            let err = state.apply_plan(&plan, &mut state_file).unwrap_err();
+            assert!(matches!(err, ApplyError::Plan(PlanError::StateChanged)));
+            assert_eq!(err.to_string(), "The state changed since the plan was made");
+
+            let mut plan = state.plan(&state_file).unwrap();
+            plan.fingerprints.as_mut().unwrap().provider.version = String::from("1.0.0");
+            let err = state.apply_plan(&plan, &mut state_file).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "The provider changed from cloud 1.0.0 to cloud 2.0.0 since the plan was made"
+            );
+            assert_eq!(state.provider().calls().len(), 1);
+        }
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                zone: String::from("eu-1"),
+                password: String::from("hunter2"),
+                size: 20,
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new());
+        let plan = Plan::load(&path).unwrap();
+        let err = state
+            .apply_plan(&plan, &mut runtime::StateFile::new())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ApplyError::Plan(PlanError::DefinitionChanged)
+        ));
+        assert!(state.provider().calls().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_apply_plan_refuses_a_changed_sensitive_input() {
+        use crate::test::cloud::prelude::*;
+        use std::sync::Mutex;
+
+        static PASSWORD: Mutex<String> = Mutex::new(String::new());
+
+        /// Reads the password at runtime, as from the environment, so the definition does not
+        /// change with it.
+        fn password() -> String {
+            PASSWORD.lock().unwrap().clone()
+        }
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                zone: String::from("eu-1"),
+                password: password(),
+                size: 10,
+            }
+        }
+
+        let state = State::from_configured(MockProvider::new());
+        let mut state_file = runtime::StateFile::new();
+        *PASSWORD.lock().unwrap() = String::from("hunter2");
+        let plan = Plan::from_json(&state.plan(&state_file).unwrap().to_json().unwrap()).unwrap();
+
+        *PASSWORD.lock().unwrap() = String::from("hunter3");
+        let err = state.apply_plan(&plan, &mut state_file).unwrap_err();
+        assert!(matches!(err, ApplyError::Plan(PlanError::ChangesDiffer)));
+        assert!(state.provider().calls().is_empty());
+
+        *PASSWORD.lock().unwrap() = String::from("hunter2");
+        state.apply_plan(&plan, &mut state_file).unwrap();
+        state.provider().assert_called(Operation::Create, "db");
+    }
+
+    #[test]
+    fn test_apply_plan_runs_the_planned_actions() {
+        use crate::test::cloud::prelude::*;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static REPLICA_SIZE: AtomicU32 = AtomicU32::new(10);
+
+        fn replica_size() -> u32 {
+            REPLICA_SIZE.load(Ordering::SeqCst)
+        }
+
+        /// Grows the replica once the primary is updated, after the plan was checked.
+        struct GrowReplica;
+
+        impl Layer for GrowReplica {
+            fn call(
+                &self,
+                ctx: &Context,
+                _inputs: &Values,
+                next: &mut Next<'_>,
+            ) -> Result<Values, ProviderError> {
+                if ctx.operation == Operation::Update {
+                    REPLICA_SIZE.store(20, Ordering::SeqCst);
+                }
+                next()
+            }
+        }
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                zone: String::from("eu-1"),
+                password: String::from("hunter2"),
+                size: 10,
+            }
+
+            #[resource(name = replica)]
+            Database {
+                zone: db.get_id(),
+                password: String::from("hunter2"),
+                size: replica_size(),
+            }
+        }
+
+        let state = State::from_configured(Layered::new(MockProvider::new()).layer(GrowReplica));
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+        record_input(&mut state_file, "size", json!(5));
+
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(plan.get("db").unwrap().action, Action::Update);
+        assert_eq!(plan.get("replica").unwrap().action, Action::NoOp);
+        state.provider().inner().clear_calls();
+
+        let err = state.apply_plan(&plan, &mut state_file).unwrap_err();
+        let errors = err.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            ApplyError::Plan(PlanError::ActionChanged {
+                resource,
+                planned: Action::NoOp,
+                current: Action::Update,
+            }) if resource == "replica"
+        ));
+        assert_eq!(
+            errors[0].to_string(),
+            "The planned action for `replica` was no-op, but it is now update"
+        );
+        state
+            .provider()
+            .inner()
+            .assert_operations(&[(Operation::Update, "db")]);
+        assert_eq!(state_file.get("replica").unwrap().inputs["size"], json!(10));
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{serde_json::json, MockProvider, Recorder, RecorderConfig, Values};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Bucket {
+            name: String,
+            #[output]
+            arn: String,
+        }
+    }
+
+    #[test]
+    fn test_replays_recorded_session() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = logs)]
+            Bucket {
+                name: String::from("logs"),
+            }
+        }
+
+        let fixture = std::env::temp_dir().join(format!(
+            "rust_iac_record_session_{}.json",
+            std::process::id()
+        ));
+        let mock = MockProvider::new().with_outputs(
+            "Bucket",
+            Values::from([(String::from("arn"), json!("arn:logs"))]),
+        );
+
+        let state = State::from_configured(Recorder::record(mock, &fixture));
+        let mut recorded_state = runtime::StateFile::new();
+        state.apply(&mut recorded_state).unwrap();
+        state.apply(&mut recorded_state).unwrap();
+        state.destroy(&mut recorded_state).unwrap();
+        assert_eq!(state.provider().interactions().len(), 3);
+        state.provider().finish().unwrap();
+
+        let state = State::<Recorder<MockProvider>>::configure(RecorderConfig::Replay {
+            path: fixture.clone(),
+        })
+        .unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+        assert_eq!(
+            state_file.get("logs").unwrap().outputs.get("arn"),
+            Some(&json!("arn:logs"))
+        );
+        assert_eq!(state_file.get("logs").unwrap().provider.name, "cloud");
+        assert!(state.provider().finish().is_err());
+
+        state.apply(&mut state_file).unwrap();
+        state.destroy(&mut state_file).unwrap();
+        state.provider().finish().unwrap();
+
+        std::fs::remove_file(&fixture).unwrap();
+    }
+
+    #[test]
+    fn test_replay_fails_on_divergence() {
+        use crate::test::cloud::prelude::*;
+
+        let fixture = std::env::temp_dir().join(format!(
+            "rust_iac_record_divergence_{}.json",
+            std::process::id()
+        ));
+
+        {
+            state! {
+                #[resource(name = logs)]
+                Bucket {
+                    name: String::from("logs"),
+                }
+            }
+
+            let state = State::<Recorder<MockProvider>>::configure(RecorderConfig::Record {
+                path: fixture.clone(),
+                inner: (),
+            })
+            .unwrap();
+            state.apply(&mut runtime::StateFile::new()).unwrap();
+        }
+
+        state! {
+            #[resource(name = logs)]
+            Bucket {
+                name: String::from("audit-logs"),
+            }
+        }
+
+        let state = State::from_configured(Recorder::<MockProvider>::replay(&fixture).unwrap());
+        let err = state.apply(&mut runtime::StateFile::new()).err().unwrap();
+
+        assert!(err
+            .to_string()
+            .contains("Replay diverged at interaction 1: expected create of `logs`"));
+
+        std::fs::remove_file(&fixture).unwrap();
+    }
+}
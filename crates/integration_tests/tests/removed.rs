@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{Action, MockProvider, MockResource, Operation, ProviderInfo, ResourceRecord};
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Disk {
+            size: u32,
+            #[output]
+            id: String,
+        }
+    }
+
+    /// A state file with the disks `old` and `data`, as applied by an earlier definition.
+    fn applied() -> runtime::StateFile {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = old)]
+            Disk {
+                size: 10,
+            }
+
+            #[resource(name = data)]
+            Disk {
+                size: 20,
+            }
+        }
+
+        let mut state_file = runtime::StateFile::new();
+        State::from_configured(MockProvider::new())
+            .apply(&mut state_file)
+            .unwrap();
+        state_file
+    }
+
+    /// A mock provider that has every resource recorded in `state_file`.
+    fn provider_of(state_file: &runtime::StateFile) -> MockProvider {
+        state_file
+            .resources()
+            .iter()
+            .fold(MockProvider::new(), |provider, record| {
+                provider.with_resource(
+                    &record.name,
+                    MockResource {
+                        resource_type: record.resource_type.clone(),
+                        inputs: record.inputs.clone(),
+                        outputs: record.outputs.clone(),
+                    },
+                )
+            })
+    }
+
+    #[test]
+    fn test_apply_deletes_resources_removed_from_the_definition() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data)]
+            Disk {
+                size: 20,
+            }
+        }
+
+        let mut state_file = applied();
+        let state = State::from_configured(provider_of(&state_file));
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("old").unwrap().action, Action::Delete);
+        state.provider().assert_called(Operation::Delete, "old");
+        assert_eq!(state.provider().resources(), vec!["data"]);
+        assert!(state_file.get("old").is_none());
+        assert!(state_file.get("data").is_some());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_apply_async_deletes_resources_removed_from_the_definition() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data)]
+            Disk {
+                size: 20,
+            }
+        }
+
+        let mut state_file = applied();
+        let state = State::from_configured(provider_of(&state_file));
+
+        let report = state.apply_async(&mut state_file).await.unwrap();
+        assert_eq!(report.get("old").unwrap().action, Action::Delete);
+        assert_eq!(state.provider().resources(), vec!["data"]);
+        assert!(state_file.get("old").is_none());
+    }
+
+    #[test]
+    fn test_destroy_deletes_removed_resources_first() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data)]
+            Disk {
+                size: 20,
+            }
+        }
+
+        let mut state_file = applied();
+        let state = State::from_configured(provider_of(&state_file));
+
+        state.destroy(&mut state_file).unwrap();
+        state
+            .provider()
+            .assert_operations(&[(Operation::Delete, "old"), (Operation::Delete, "data")]);
+        assert!(state.provider().resources().is_empty());
+        assert!(state_file.resources().is_empty());
+    }
+
+    #[test]
+    fn test_records_of_other_providers_and_types_are_left_alone() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data)]
+            Disk {
+                size: 20,
+            }
+        }
+
+        let mut state_file = applied();
+        let provider = state_file.get("data").unwrap().provider.clone();
+        state_file.record(ResourceRecord::new(
+            "bucket",
+            "Bucket",
+            ProviderInfo::new("storage", "1.0.0"),
+        ));
+        state_file.record(ResourceRecord::new("queue", "Queue", provider));
+        let state = State::from_configured(provider_of(&state_file));
+
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(plan.get("old").unwrap().action, Action::Delete);
+        assert!(plan.get("bucket").is_none());
+        assert!(plan.get("queue").is_none());
+
+        let report = state.apply(&mut state_file).unwrap();
+        let warned = report
+            .diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.resource.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(warned, vec!["queue"]);
+        assert!(report.diagnostics[0]
+            .message
+            .contains("recorded as type `Queue`"));
+        state.provider().assert_called(Operation::Delete, "old");
+
+        state.provider().clear_calls();
+        state.destroy(&mut state_file).unwrap();
+        state
+            .provider()
+            .assert_operations(&[(Operation::Delete, "data")]);
+        assert!(state_file.get("bucket").is_some());
+        assert!(state_file.get("queue").is_some());
+    }
+}
@@ -0,0 +1,274 @@
+#[cfg(test)]
+mod test {
+
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use provider_macro::provider;
+    use runtime::{
+        rest::{RestConfig, RestProvider, Route},
+        serde_json::{json, Value},
+    };
+    use state_macro::state;
+
+    #[provider(name = "shop", version = "1.0.0", rest)]
+    mod shop {
+
+        #[resource_definition(derive(Resource))]
+        struct Item {
+            name: String,
+            #[input(default = 1)]
+            quantity: u32,
+            #[output]
+            id: u64,
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Request {
+        method: String,
+        path: String,
+        body: Option<Value>,
+    }
+
+    /// Serves a single `/items` collection, answering every request with the stored item.
+    struct StubServer {
+        url: String,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl StubServer {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = requests.clone();
+
+            thread::spawn(move || {
+                let mut item = json!({});
+
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+                    let body: Option<Value> =
+                        (!body.is_empty()).then(|| runtime::serde_json::from_slice(&body).unwrap());
+
+                    let (status, response) = match (method.as_str(), path.as_str(), &body) {
+                        ("POST", "/items", Some(body)) => {
+                            item = body.clone();
+                            item["id"] = json!(7);
+                            ("201 Created", item.to_string())
+                        }
+                        ("GET", "/items/7", _) => ("200 OK", item.to_string()),
+                        ("PUT", "/items/7", Some(body)) => {
+                            item = body.clone();
+                            item["id"] = json!(7);
+                            ("200 OK", item.to_string())
+                        }
+                        ("DELETE", "/items/7", _) => ("204 No Content", String::new()),
+                        _ => ("404 Not Found", String::from("no such item")),
+                    };
+
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push(Request { method, path, body });
+
+                    write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        response.len(),
+                        response
+                    )
+                    .unwrap();
+                }
+            });
+
+            Self { url, requests }
+        }
+
+        fn requests(&self) -> Vec<(String, String)> {
+            self.requests
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|r| (r.method.clone(), r.path.clone()))
+                .collect()
+        }
+    }
+
+    fn request(method: &str, path: &str) -> (String, String) {
+        (method.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn test_rest_provider_lifecycle() {
+        use crate::test::shop::prelude::*;
+
+        state! {
+            #[resource(name = book)]
+            Item {
+                name: String::from("book"),
+            }
+        }
+
+        let server = StubServer::start();
+        let config = RestConfig::new(&server.url).route("Item", Route::collection("/items"));
+        let state = State::<RestProvider>::configure(config).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+        let record = state_file.get("book").unwrap();
+        assert_eq!(record.outputs.get("id"), Some(&json!(7)));
+        assert_eq!(
+            server.requests.lock().unwrap()[0].body,
+            Some(json!({"name": "book", "quantity": 1}))
+        );
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("book").unwrap().action, runtime::Action::NoOp);
+
+        let mut record = state_file.get("book").unwrap().clone();
+        record.inputs.insert(String::from("quantity"), json!(2));
+        state_file.record(record);
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("book").unwrap().action, runtime::Action::Update);
+
+        state.destroy(&mut state_file).unwrap();
+        assert!(state_file.resources().is_empty());
+
+        assert_eq!(
+            server.requests(),
+            vec![
+                request("POST", "/items"),
+                request("GET", "/items/7"),
+                request("PUT", "/items/7"),
+                request("DELETE", "/items/7"),
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_rest_provider_applies_async() {
+        use crate::test::shop::prelude::*;
+
+        state! {
+            #[resource(name = book)]
+            Item {
+                name: String::from("book"),
+            }
+        }
+
+        let server = StubServer::start();
+        let config = RestConfig::new(&server.url).route("Item", Route::collection("/items"));
+        let state = State::<RestProvider>::configure(config).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply_async(&mut state_file).await.unwrap();
+        let record = state_file.get("book").unwrap();
+        assert_eq!(record.outputs.get("id"), Some(&json!(7)));
+
+        state.destroy_async(&mut state_file).await.unwrap();
+        assert!(state_file.resources().is_empty());
+
+        assert_eq!(
+            server.requests(),
+            vec![request("POST", "/items"), request("DELETE", "/items/7")]
+        );
+    }
+
+    #[test]
+    fn test_rest_provider_reports_error_status() {
+        use crate::test::shop::prelude::*;
+
+        state! {
+            #[resource(name = book)]
+            Item {
+                name: String::from("book"),
+            }
+        }
+
+        let server = StubServer::start();
+        let config = RestConfig::new(&server.url).route("Item", Route::collection("/missing"));
+        let state = State::<RestProvider>::configure(config).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(matches!(
+            err.errors()[0],
+            runtime::ApplyError::Provider {
+                operation: runtime::Operation::Create,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("returned status 404"));
+        assert!(state_file.get("book").is_none());
+    }
+
+    #[test]
+    fn test_rest_provider_does_not_resend_a_create_whose_response_was_lost() {
+        use crate::test::shop::prelude::*;
+
+        state! {
+            #[resource(name = book)]
+            Item {
+                name: String::from("book"),
+            }
+        }
+
+        // Reads each request and hangs up without answering.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(0));
+        let counter = received.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut request_line = String::new();
+                BufReader::new(stream.unwrap())
+                    .read_line(&mut request_line)
+                    .unwrap();
+                *counter.lock().unwrap() += 1;
+            }
+        });
+
+        let config = RestConfig::new(&url).route("Item", Route::collection("/items"));
+        let state = State::<RestProvider>::configure(config).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(err.errors()[0].is_timeout());
+        assert_eq!(
+            state_file.get("book").unwrap().status,
+            runtime::ResourceStatus::Uncertain
+        );
+        assert_eq!(*received.lock().unwrap(), 1);
+    }
+}
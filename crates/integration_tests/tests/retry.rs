@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test {
+
+    use provider_macro::provider;
+    use runtime::{Fault, FaultInjector, FaultKind, Operation, ProviderError};
+    use state_macro::state;
+
+    #[provider(name = "flaky", version = "1.0.0")]
+    mod flaky {
+
+        #[provider_definition]
+        struct FlakyProvider;
+
+        #[provider_implementation]
+        impl Provider for FlakyProvider {
+            type Config = ();
+
+            fn configure(_config: ()) -> Result<Self, runtime::ConfigError> {
+                Ok(Self)
+            }
+
+            fn retry_policy(&self) -> runtime::RetryPolicy {
+                runtime::RetryPolicy::new(4).backoff(
+                    std::time::Duration::from_millis(1),
+                    std::time::Duration::from_millis(5),
+                )
+            }
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct Queue {
+            name: String,
+        }
+
+        #[resource_definition]
+        struct Payment {
+            amount: u32,
+        }
+
+        #[resource_implementation]
+        impl Resource for Payment {
+            type Payload = ();
+
+            fn payload(&self) -> Self::Payload {}
+
+            fn retry_policy(&self) -> Option<runtime::RetryPolicy> {
+                Some(runtime::RetryPolicy::none())
+            }
+        }
+    }
+
+    fn throttled() -> Fault {
+        Fault::new(FaultKind::Error(ProviderError::retryable("throttled")))
+    }
+
+    #[test]
+    fn test_retries_retryable_errors() {
+        use crate::test::flaky::prelude::*;
+
+        state! {
+            #[resource(name = jobs)]
+            Queue {
+                name: String::from("jobs"),
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(FlakyProvider)
+                .with_fault(throttled().on(Operation::Create).times(2)),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("jobs").unwrap().attempts, 3);
+        assert!(state_file.get("jobs").is_some());
+
+        state.provider().add_fault(throttled().always());
+        let err = state.destroy(&mut state_file).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Failed to delete 'jobs' after 4 attempts: throttled"
+        );
+    }
+
+    #[test]
+    fn test_does_not_retry_fatal_errors_or_resources_without_retries() {
+        use crate::test::flaky::prelude::*;
+
+        state! {
+            #[resource(name = jobs)]
+            Queue {
+                name: String::from("jobs"),
+            }
+
+            #[resource(name = charge)]
+            Payment {
+                amount: 10,
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(FlakyProvider)
+                .with_fault(Fault::error("forbidden").resource("jobs")),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert_eq!(err.to_string(), "Failed to create 'jobs': forbidden");
+        assert!(state_file.get("charge").is_some());
+
+        state.provider().add_fault(throttled().resource("charge"));
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert_eq!(err.to_string(), "Failed to read 'charge': throttled");
+        assert_eq!(state.provider().injected().len(), 2);
+    }
+}
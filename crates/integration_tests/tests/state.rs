@@ -4,18 +4,40 @@ mod test {
     use provider_macro::provider;
     use state_macro::state;
 
-    #[provider]
+    fn config() -> test_provider::prelude::Config {
+        test_provider::prelude::Config {
+            url: String::from("http://test.com"),
+            timeout_secs: None,
+        }
+    }
+
+    #[provider(name = "test", version = "1.4.0")]
     mod test_provider {
 
+        #[provider_config]
+        struct Config {
+            url: String,
+            timeout_secs: Option<u64>,
+        }
+
         #[provider_definition]
-        struct TestProvider;
+        struct TestProvider {
+            url: String,
+        }
 
         #[provider_implementation]
         impl Provider for TestProvider {
-            const url: &'static str = "http://test.com";
+            type Config = Config;
 
-            fn get<R: Resource>(&self, resource: &R) -> R::Payload {
-                resource.payload()
+            fn configure(config: Config) -> Result<Self, runtime::ConfigError> {
+                if !config.url.starts_with("http") {
+                    return Err(runtime::ConfigError::invalid(
+                        "url",
+                        "expected an http(s) url",
+                    ));
+                }
+
+                Ok(Self { url: config.url })
             }
         }
 
@@ -33,6 +55,7 @@ mod test {
 
         #[resource_definition(outputs = {id: i32})]
         struct TestResourceB {
+            #[input(rename = "input_id")]
             id: i32,
         }
 
@@ -46,6 +69,60 @@ mod test {
                 self.set_id(420);
             }
         }
+
+        #[resource_definition]
+        struct TestResourceC {
+            #[input]
+            name: String,
+            #[input(optional)]
+            tags: Vec<String>,
+            #[input(default = 3)]
+            replicas: u32,
+            #[output]
+            id: String,
+        }
+
+        #[resource_implementation]
+        impl Resource for TestResourceC {
+            type Payload = ();
+
+            fn payload(&self) -> Self::Payload {}
+
+            fn set_outputs(&mut self) {
+                self.set_id(format!(
+                    "{}-{}-{}",
+                    self.name,
+                    self.tags.len(),
+                    self.replicas
+                ));
+            }
+        }
+
+        #[resource_definition(derive(Resource))]
+        struct TestResourceD {
+            name: String,
+            #[input(optional)]
+            tags: Vec<String>,
+            #[input(default = 1)]
+            replicas: u32,
+            #[output]
+            id: String,
+        }
+    }
+
+    #[test]
+    fn test_derived_resource() {
+        use crate::test::test_provider::prelude::*;
+
+        let resource = TestResourceD::builder()
+            .name(String::from("web"))
+            .tags(vec![String::from("prod")])
+            .build();
+        let payload: TestResourceDPayload = resource.payload();
+
+        assert_eq!(payload.name, "web");
+        assert_eq!(payload.tags, vec![String::from("prod")]);
+        assert_eq!(payload.replicas, 1);
     }
 
     #[test]
@@ -63,9 +140,240 @@ mod test {
             TestResourceB {
                 id: 10,
             }
+
+            #[resource(name = resource_c)]
+            TestResourceC {
+                name: String::from("web"),
+            }
+        }
+
+        let state = State::<TestProvider>::configure(config()).unwrap();
+        let plan = state.plan(&runtime::StateFile::new()).unwrap();
+
+        assert_eq!(plan.count(runtime::Action::Create), 3);
+    }
+
+    #[test]
+    fn test_provider_config() {
+        use crate::test::test_provider::prelude::*;
+
+        state! {
+            #[resource(name = resource_b)]
+            TestResourceB {
+                id: 10,
+            }
         }
 
-        let state = State::new(TestProvider);
-        state.plan();
+        let path = std::env::temp_dir().join(format!(
+            "rust_iac_test_provider_config_{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, "url = \"http://localhost:8080\"\ntimeout_secs = 5\n").unwrap();
+        let config = Config::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.timeout_secs, Some(5));
+        State::<TestProvider>::configure(config)
+            .unwrap()
+            .plan(&runtime::StateFile::new())
+            .unwrap();
+
+        let source = runtime::ConfigSource::from_vars(
+            "TEST_PROVIDER",
+            [(
+                String::from("TEST_PROVIDER_URL"),
+                String::from("ftp://localhost"),
+            )],
+        );
+        let config = Config::from_source(&source).unwrap();
+
+        assert_eq!(config.timeout_secs, None);
+        assert!(matches!(
+            State::<TestProvider>::configure(config),
+            Err(runtime::ConfigError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_field_attributes() {
+        use crate::test::test_provider::prelude::*;
+
+        let mut resource = TestResourceC::new(String::from("web"));
+        resource.set_outputs();
+
+        assert_eq!(resource.get_id(), "web-0-3");
+    }
+
+    #[test]
+    fn test_builder_applies_defaults() {
+        use crate::test::test_provider::prelude::*;
+
+        let mut resource = TestResourceC::builder()
+            .name(String::from("web"))
+            .replicas(5)
+            .build();
+        resource.set_outputs();
+
+        assert_eq!(resource.get_id(), "web-0-5");
+    }
+
+    #[test]
+    #[should_panic(expected = "`TestResourceC` is missing required input `name`")]
+    fn test_builder_panics_on_missing_required_input() {
+        use crate::test::test_provider::prelude::*;
+
+        TestResourceC::builder().replicas(5).build();
+    }
+
+    #[test]
+    fn test_provider_metadata_is_recorded_in_state() {
+        use crate::test::test_provider::prelude::*;
+
+        state! {
+            #[resource(name = resource_b)]
+            TestResourceB {
+                id: 10,
+            }
+        }
+
+        assert_eq!(TestProvider::NAME, "test");
+        assert_eq!(TestProvider::VERSION, "1.4.0");
+
+        let state = State::<TestProvider>::configure(config()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+        let diagnostics = state.apply(&mut state_file).unwrap().diagnostics;
+
+        let record = state_file.get("resource_b").unwrap();
+        assert!(diagnostics.is_empty());
+        assert_eq!(record.resource_type, "TestResourceB");
+        assert_eq!(record.provider, runtime::ProviderInfo::new("test", "1.4.0"));
+
+        let mut old_state_file = runtime::StateFile::new();
+        old_state_file.record(runtime::ResourceRecord::new(
+            "resource_b",
+            "TestResourceB",
+            runtime::ProviderInfo::new("test", "0.9.0"),
+        ));
+        let diagnostics = state.apply(&mut old_state_file).unwrap().diagnostics;
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, runtime::Severity::Warning);
+    }
+
+    #[test]
+    fn test_resource_serialization() {
+        use crate::test::test_provider::prelude::*;
+
+        let mut resource = TestResourceB::builder().id(10).build();
+        resource.set_outputs();
+
+        let json = runtime::serde_json::to_value(&resource).unwrap();
+        assert_eq!(
+            json,
+            runtime::serde_json::json!({"input_id": 10, "id": 420})
+        );
+
+        let resource: TestResourceB =
+            runtime::serde_json::from_value(runtime::serde_json::json!({"input_id": 3})).unwrap();
+        assert_eq!(resource.get_id(), 0);
+
+        let mut resource: TestResourceC =
+            runtime::serde_json::from_value(runtime::serde_json::json!({"name": "web"})).unwrap();
+        resource.set_outputs();
+        assert_eq!(resource.get_id(), "web-0-3");
+
+        let payload = TestResourceD::builder()
+            .name(String::from("web"))
+            .build()
+            .payload();
+        assert_eq!(
+            runtime::serde_json::to_value(payload).unwrap(),
+            runtime::serde_json::json!({"name": "web", "tags": [], "replicas": 1})
+        );
+    }
+
+    #[test]
+    fn test_provider_gets_declared_payload_by_default() {
+        use crate::test::test_provider::prelude::*;
+
+        let provider = TestProvider::configure(config()).unwrap();
+        let resource = TestResourceD::builder().name(String::from("web")).build();
+
+        assert_eq!(
+            runtime::serde_json::to_value(provider.get(&resource)).unwrap(),
+            runtime::serde_json::json!({"name": "web", "tags": [], "replicas": 1})
+        );
+    }
+
+    #[test]
+    fn test_values_are_recorded_in_state() {
+        use crate::test::test_provider::prelude::*;
+
+        state! {
+            #[resource(name = resource_c)]
+            TestResourceC {
+                name: String::from("web"),
+            }
+        }
+
+        let state = State::<TestProvider>::configure(config()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+
+        let record = state_file.get("resource_c").unwrap();
+        assert_eq!(
+            record.inputs,
+            runtime::Values::from([
+                (String::from("name"), runtime::serde_json::json!("web")),
+                (String::from("tags"), runtime::serde_json::json!([])),
+                (String::from("replicas"), runtime::serde_json::json!(3)),
+            ])
+        );
+        assert_eq!(
+            record.outputs,
+            runtime::Values::from([(String::from("id"), runtime::serde_json::json!("web-0-3"))])
+        );
+    }
+
+    #[test]
+    fn test_apply_lifecycle() {
+        use crate::test::test_provider::prelude::*;
+
+        state! {
+            #[resource(name = resource_a)]
+            TestResourceA {
+                id: resource_b.get_id(),
+            }
+
+            #[resource(name = resource_b)]
+            TestResourceB {
+                id: 10,
+            }
+        }
+
+        let state = State::<TestProvider>::configure(config()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(
+            report.get("resource_b").unwrap().action,
+            runtime::Action::Create
+        );
+        assert_eq!(
+            state_file.get("resource_a").unwrap().inputs,
+            runtime::Values::from([(String::from("id"), runtime::serde_json::json!(420))])
+        );
+
+        let plan = state.plan(&state_file).unwrap();
+        assert!(!plan.has_changes());
+
+        let report = state.destroy(&mut state_file).unwrap();
+        let destroyed = report
+            .resources
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(destroyed, vec!["resource_a", "resource_b"]);
+        assert!(state_file.resources().is_empty());
     }
 }
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod test {
+
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use provider_macro::provider;
+    use runtime::{
+        Action, Context, Fault, FaultInjector, Layer, Layered, MockProvider, Next, Operation,
+        ProviderError, ResourceStatus, Values,
+    };
+    use state_macro::state;
+
+    #[provider(name = "cloud", version = "2.0.0", mock)]
+    mod cloud {
+
+        #[resource_definition(derive(Resource))]
+        struct Disk {
+            size: u32,
+        }
+
+        #[resource_definition]
+        struct Database {
+            engine: String,
+        }
+
+        #[resource_implementation]
+        impl Resource for Database {
+            type Payload = ();
+
+            fn payload(&self) -> Self::Payload {}
+
+            fn timeouts(&self) -> runtime::Timeouts {
+                runtime::Timeouts::new().delete(std::time::Duration::from_millis(20))
+            }
+        }
+    }
+
+    fn hang() -> Fault {
+        Fault::timeout(Duration::from_secs(10))
+    }
+
+    /// Hangs every call until its deadline, then gives up as a provider should.
+    struct Hang;
+
+    impl Layer for Hang {
+        fn call(
+            &self,
+            ctx: &Context,
+            _inputs: &Values,
+            _next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            thread::sleep(ctx.remaining().expect("the call is given a deadline"));
+            Err(ctx.timed_out())
+        }
+    }
+
+    #[test]
+    fn test_hung_provider_call_is_given_up_at_the_deadline() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data, timeouts(create = "20ms"))]
+            Disk {
+                size: 100,
+            }
+        }
+
+        let state = State::from_configured(Layered::new(MockProvider::new()).layer(Hang));
+        let mut state_file = runtime::StateFile::new();
+
+        let start = Instant::now();
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err.errors()[0].is_timeout());
+        assert_eq!(
+            err.to_string(),
+            "Timed out waiting to create 'data': create of `data` did not finish by its deadline"
+        );
+        assert_eq!(
+            state_file.get("data").unwrap().status,
+            ResourceStatus::Uncertain
+        );
+    }
+
+    #[test]
+    fn test_create_timeout_marks_resource_uncertain() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data, timeouts(create = "20ms"))]
+            Disk {
+                size: 100,
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(MockProvider::new()).with_fault(hang().on(Operation::Create)),
+        );
+        let mut state_file = runtime::StateFile::new();
+
+        let start = Instant::now();
+        let err = state.apply(&mut state_file).err().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err.errors()[0].is_timeout());
+        assert!(err.errors()[0].to_diagnostic().to_string().starts_with(
+            "error [data]: timed out waiting to create, the resource is now uncertain"
+        ));
+
+        assert_eq!(
+            state_file.get("data").unwrap().status,
+            ResourceStatus::Uncertain
+        );
+        assert_eq!(
+            state.plan(&state_file).unwrap().get("data").unwrap().action,
+            Action::Update
+        );
+    }
+
+    #[test]
+    fn test_apply_after_create_timeout_creates_missing_resource() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = data, timeouts(create = "20ms"))]
+            Disk {
+                size: 100,
+            }
+        }
+
+        let state = State::from_configured(
+            FaultInjector::new(MockProvider::new()).with_fault(hang().on(Operation::Create)),
+        );
+        let mut state_file = runtime::StateFile::new();
+        assert!(state.apply(&mut state_file).err().unwrap().errors()[0].is_timeout());
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("data").unwrap().action, Action::Create);
+        assert_eq!(
+            state_file.get("data").unwrap().status,
+            ResourceStatus::Ready
+        );
+        state
+            .provider()
+            .inner()
+            .assert_operations(&[(Operation::Read, "data"), (Operation::Create, "data")]);
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(report.get("data").unwrap().action, Action::NoOp);
+    }
+
+    #[test]
+    fn test_delete_timeout_uses_resource_type_default() {
+        use crate::test::cloud::prelude::*;
+
+        state! {
+            #[resource(name = db)]
+            Database {
+                engine: String::from("postgres"),
+            }
+        }
+
+        let state = State::from_configured(FaultInjector::new(MockProvider::new()));
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+
+        state.provider().add_fault(hang().on(Operation::Delete));
+        let start = Instant::now();
+        let err = state.destroy(&mut state_file).err().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err
+            .to_string()
+            .starts_with("Timed out waiting to delete 'db'"));
+        assert_eq!(
+            state_file.get("db").unwrap().status,
+            ResourceStatus::Uncertain
+        );
+
+        state.destroy(&mut state_file).unwrap();
+        assert!(state_file.get("db").is_none());
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod test {
+
+    use std::collections::BTreeMap;
+
+    use providers::utility::prelude::*;
+    use runtime::serde_json::json;
+    use state_macro::state;
+
+    fn output(state_file: &runtime::StateFile, name: &str, field: &str) -> runtime::Value {
+        state_file.get(name).unwrap().outputs[field].clone()
+    }
+
+    #[test]
+    fn test_random_values_are_stable_until_keepers_change() {
+        state! {
+            #[resource(name = suffix)]
+            RandomId {
+                keepers: BTreeMap::from([(String::from("env"), String::from("prod"))]),
+                byte_length: 4,
+            }
+
+            #[resource(name = password)]
+            RandomPassword {
+                length: 24,
+                special: false,
+            }
+
+            #[resource(name = uuid)]
+            RandomUuid {}
+        }
+
+        let state = State::<UtilityProvider>::configure(()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+
+        state.apply(&mut state_file).unwrap();
+        let suffix = output(&state_file, "suffix", "hex");
+        let password = output(&state_file, "password", "result");
+        let uuid = output(&state_file, "uuid", "result");
+
+        assert_eq!(suffix.as_str().unwrap().len(), 8);
+        assert_eq!(password.as_str().unwrap().len(), 24);
+        assert!(password
+            .as_str()
+            .unwrap()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric()));
+        assert_eq!(uuid.as_str().unwrap().len(), 36);
+
+        state.apply(&mut state_file).unwrap();
+        assert_eq!(output(&state_file, "suffix", "hex"), suffix);
+        assert_eq!(output(&state_file, "password", "result"), password);
+        assert_eq!(output(&state_file, "uuid", "result"), uuid);
+
+        let mut record = state_file.get("suffix").unwrap().clone();
+        record
+            .inputs
+            .insert(String::from("keepers"), json!({"env": "dev"}));
+        state_file.record(record);
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(
+            report.get("suffix").unwrap().action,
+            runtime::Action::Update
+        );
+        assert_ne!(output(&state_file, "suffix", "hex"), suffix);
+        assert_eq!(output(&state_file, "password", "result"), password);
+    }
+
+    #[test]
+    fn test_plan_masks_generated_passwords() {
+        state! {
+            #[resource(name = password)]
+            RandomPassword {
+                keepers: BTreeMap::from([(String::from("env"), String::from("prod"))]),
+            }
+        }
+
+        let state = State::<UtilityProvider>::configure(()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+        let password = output(&state_file, "password", "result");
+        let password = password.as_str().unwrap();
+
+        assert!(!state
+            .plan(&state_file)
+            .unwrap()
+            .to_json()
+            .unwrap()
+            .contains(password));
+
+        let mut record = state_file.get("password").unwrap().clone();
+        record
+            .inputs
+            .insert(String::from("keepers"), json!({"env": "dev"}));
+        state_file.record(record);
+
+        let plan = state.plan(&state_file).unwrap();
+        assert_eq!(plan.get("password").unwrap().sensitive, vec!["result"]);
+        let rendered = plan.to_string();
+        assert!(rendered.contains("      ~ result  = (known after apply)\n"));
+        assert!(!rendered.contains(password));
+        assert!(!plan.to_json().unwrap().contains(password));
+    }
+
+    #[test]
+    fn test_null_resource_retriggers_dependents() {
+        state! {
+            #[resource(name = trigger)]
+            Null {
+                triggers: BTreeMap::from([(String::from("version"), String::from("2"))]),
+            }
+
+            #[resource(name = after)]
+            Null {
+                triggers: BTreeMap::from([(String::from("after"), trigger.get_id())]),
+            }
+        }
+
+        let state = State::<UtilityProvider>::configure(()).unwrap();
+        let mut state_file = runtime::StateFile::new();
+        state.apply(&mut state_file).unwrap();
+        let id = output(&state_file, "trigger", "id");
+
+        let mut record = state_file.get("trigger").unwrap().clone();
+        record
+            .inputs
+            .insert(String::from("triggers"), json!({"version": "1"}));
+        state_file.record(record);
+
+        let report = state.apply(&mut state_file).unwrap();
+        assert_eq!(
+            report.get("trigger").unwrap().action,
+            runtime::Action::Update
+        );
+        assert_eq!(report.get("after").unwrap().action, runtime::Action::Update);
+        assert_ne!(output(&state_file, "trigger", "id"), id);
+    }
+}
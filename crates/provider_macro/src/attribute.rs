@@ -1,14 +1,18 @@
 use syn::{
     parse::{Parse, ParseStream},
     token::Paren,
-    PatType, Token,
+    Expr, Ident, LitStr, PatType, Token,
 };
 
 pub(crate) enum Attribute {
-    ResourceDefinition { outputs: Vec<PatType> },
+    ResourceDefinition {
+        outputs: Vec<PatType>,
+        derive_resource: bool,
+    },
     ResourceImplementation,
     ProviderDefintion,
     ProviderImplementation,
+    ProviderConfig,
 }
 
 impl Parse for Attribute {
@@ -21,26 +25,58 @@ impl Parse for Attribute {
             content.parse::<keyword::resource_definition>()?;
 
             let mut outputs: Vec<PatType> = Vec::new();
+            let mut derive_resource = false;
 
             if content.peek(Paren) {
                 let resource_def_content;
                 syn::parenthesized!(resource_def_content in content);
-                resource_def_content.parse::<keyword::outputs>()?;
-                resource_def_content.parse::<Token![=]>()?;
 
-                let outputs_content;
-                syn::braced!(outputs_content in resource_def_content);
+                while !resource_def_content.is_empty() {
+                    if resource_def_content.peek(keyword::outputs) {
+                        resource_def_content.parse::<keyword::outputs>()?;
+                        resource_def_content.parse::<Token![=]>()?;
 
-                while !outputs_content.is_empty() {
-                    outputs.push(outputs_content.parse::<PatType>()?);
+                        let outputs_content;
+                        syn::braced!(outputs_content in resource_def_content);
 
-                    if outputs_content.peek(Token![,]) {
-                        outputs_content.parse::<Token![,]>()?;
+                        while !outputs_content.is_empty() {
+                            outputs.push(outputs_content.parse::<PatType>()?);
+
+                            if outputs_content.peek(Token![,]) {
+                                outputs_content.parse::<Token![,]>()?;
+                            }
+                        }
+                    } else if resource_def_content.peek(keyword::derive) {
+                        resource_def_content.parse::<keyword::derive>()?;
+
+                        let derive_content;
+                        syn::parenthesized!(derive_content in resource_def_content);
+                        let derived = derive_content.parse::<Ident>()?;
+
+                        if derived != "Resource" || !derive_content.is_empty() {
+                            return Err(syn::Error::new(
+                                derived.span(),
+                                "Only `Resource` can be derived",
+                            ));
+                        }
+
+                        derive_resource = true;
+                    } else {
+                        return Err(resource_def_content.error(
+                            "Expected `outputs = {..}` or `derive(Resource)` in resource definition",
+                        ));
+                    }
+
+                    if resource_def_content.peek(Token![,]) {
+                        resource_def_content.parse::<Token![,]>()?;
                     }
                 }
             }
 
-            Ok(Self::ResourceDefinition { outputs })
+            Ok(Self::ResourceDefinition {
+                outputs,
+                derive_resource,
+            })
         } else if content.peek(keyword::provider_definition) {
             content.parse::<keyword::provider_definition>()?;
             Ok(Self::ProviderDefintion)
@@ -50,18 +86,125 @@ impl Parse for Attribute {
         } else if content.peek(keyword::provider_implementation) {
             content.parse::<keyword::provider_implementation>()?;
             Ok(Self::ProviderImplementation)
+        } else if content.peek(keyword::provider_config) {
+            content.parse::<keyword::provider_config>()?;
+            Ok(Self::ProviderConfig)
         } else {
             Err(content.error("Expected keyword not found"))
         }
     }
 }
 
+pub(crate) struct FieldAttribute {
+    pub(crate) kind: FieldKind,
+    pub(crate) rename: Option<LitStr>,
+    pub(crate) sensitive: bool,
+    pub(crate) force_new: bool,
+}
+
+pub(crate) enum FieldKind {
+    Input(InputKind),
+    Output,
+}
+
+pub(crate) enum InputKind {
+    Required,
+    Optional,
+    Default(Expr),
+}
+
+impl FieldAttribute {
+    pub(crate) fn is_field_attribute(attr: &syn::Attribute) -> bool {
+        attr.path().is_ident("input") || attr.path().is_ident("output")
+    }
+}
+
+impl Parse for FieldAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![#]>()?;
+        let content;
+        syn::bracketed!(content in input);
+
+        let mut kind = if content.peek(keyword::output) {
+            content.parse::<keyword::output>()?;
+            FieldKind::Output
+        } else if content.peek(keyword::input) {
+            content.parse::<keyword::input>()?;
+            FieldKind::Input(InputKind::Required)
+        } else {
+            return Err(content.error("Expected keyword not found"));
+        };
+        let mut rename: Option<LitStr> = None;
+        let mut sensitive = false;
+        let mut force_new = false;
+
+        if content.peek(Paren) {
+            let options_content;
+            syn::parenthesized!(options_content in content);
+
+            while !options_content.is_empty() {
+                if options_content.peek(keyword::rename) {
+                    options_content.parse::<keyword::rename>()?;
+                    options_content.parse::<Token![=]>()?;
+                    rename = Some(options_content.parse::<LitStr>()?);
+                } else if options_content.peek(keyword::sensitive) {
+                    options_content.parse::<keyword::sensitive>()?;
+                    sensitive = true;
+                } else if matches!(kind, FieldKind::Input(_))
+                    && options_content.peek(keyword::force_new)
+                {
+                    options_content.parse::<keyword::force_new>()?;
+                    force_new = true;
+                } else if matches!(kind, FieldKind::Input(_))
+                    && options_content.peek(keyword::optional)
+                {
+                    options_content.parse::<keyword::optional>()?;
+                    kind = FieldKind::Input(InputKind::Optional);
+                } else if matches!(kind, FieldKind::Input(_))
+                    && options_content.peek(keyword::default)
+                {
+                    options_content.parse::<keyword::default>()?;
+                    options_content.parse::<Token![=]>()?;
+                    kind = FieldKind::Input(InputKind::Default(options_content.parse::<Expr>()?));
+                } else if matches!(kind, FieldKind::Input(_)) {
+                    return Err(options_content.error(
+                        "Expected `optional`, `default = ..`, `rename = ..`, `sensitive` or `force_new` in input attribute",
+                    ));
+                } else {
+                    return Err(options_content
+                        .error("Expected `rename = ..` or `sensitive` in output attribute"));
+                }
+
+                if options_content.peek(Token![,]) {
+                    options_content.parse::<Token![,]>()?;
+                }
+            }
+        }
+
+        Ok(Self {
+            kind,
+            rename,
+            sensitive,
+            force_new,
+        })
+    }
+}
+
 mod keyword {
     syn::custom_keyword!(resource_definition);
     syn::custom_keyword!(resource_implementation);
     syn::custom_keyword!(provider_definition);
     syn::custom_keyword!(provider_implementation);
+    syn::custom_keyword!(provider_config);
     syn::custom_keyword!(outputs);
+    syn::custom_keyword!(derive);
+    syn::custom_keyword!(input);
+    syn::custom_keyword!(output);
+    syn::custom_keyword!(optional);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(sensitive);
+    syn::custom_keyword!(force_new);
 }
 
 #[cfg(test)]
@@ -87,7 +230,7 @@ mod test {
 
         let result: Attribute = parse2(input).unwrap();
 
-        if let Attribute::ResourceDefinition { outputs } = result {
+        if let Attribute::ResourceDefinition { outputs, .. } = result {
             assert_eq!(outputs.len(), 1);
         } else {
             panic!("parsed to incorrect attribute");
@@ -102,13 +245,44 @@ mod test {
 
         let result: Attribute = parse2(input).unwrap();
 
-        if let Attribute::ResourceDefinition { outputs } = result {
+        if let Attribute::ResourceDefinition { outputs, .. } = result {
             assert_eq!(outputs.len(), 2);
         } else {
             panic!("parsed to incorrect attribute");
         }
     }
 
+    #[test]
+    fn test_resource_provider_attribute_parses_resource_def_with_derive_correctly() {
+        let input = quote! {
+            #[resource_definition(outputs = {foo: String}, derive(Resource))]
+        };
+
+        let result: Attribute = parse2(input).unwrap();
+
+        if let Attribute::ResourceDefinition {
+            outputs,
+            derive_resource,
+        } = result
+        {
+            assert_eq!(outputs.len(), 1);
+            assert!(derive_resource);
+        } else {
+            panic!("parsed to incorrect attribute");
+        }
+    }
+
+    #[test]
+    fn test_resource_provider_attribute_returns_error_for_unknown_derive() {
+        let input = quote! {
+            #[resource_definition(derive(Provider))]
+        };
+
+        let err = parse2::<Attribute>(input).err().unwrap();
+
+        assert_eq!(err.to_string(), "Only `Resource` can be derived");
+    }
+
     #[test]
     fn test_resource_provider_attribute_parses_provider_def_correctly() {
         let input = quote! {
@@ -135,4 +309,131 @@ mod test {
 
         let _result: Attribute = parse2(input).unwrap();
     }
+
+    #[test]
+    fn test_resource_provider_attribute_parses_provider_config_correctly() {
+        let input = quote! {
+            #[provider_config]
+        };
+
+        let result: Attribute = parse2(input).unwrap();
+
+        assert!(matches!(result, Attribute::ProviderConfig));
+    }
+
+    #[test]
+    fn test_field_attribute_parses_output_correctly() {
+        let input = quote! {
+            #[output]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(result.kind, FieldKind::Output));
+    }
+
+    #[test]
+    fn test_field_attribute_parses_required_input_correctly() {
+        let input = quote! {
+            #[input]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(result.kind, FieldKind::Input(InputKind::Required)));
+    }
+
+    #[test]
+    fn test_field_attribute_parses_optional_input_correctly() {
+        let input = quote! {
+            #[input(optional)]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(result.kind, FieldKind::Input(InputKind::Optional)));
+    }
+
+    #[test]
+    fn test_field_attribute_parses_default_input_correctly() {
+        let input = quote! {
+            #[input(default = 3)]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(
+            result.kind,
+            FieldKind::Input(InputKind::Default(Expr::Lit(_)))
+        ));
+    }
+
+    #[test]
+    fn test_field_attribute_returns_error_for_unknown_input_option() {
+        let input = quote! {
+            #[input(foo)]
+        };
+
+        let err = parse2::<FieldAttribute>(input).err().unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Expected `optional`, `default = ..`, `rename = ..`, `sensitive` or `force_new` in input attribute"
+        );
+
+        let input = quote! {
+            #[output(force_new)]
+        };
+
+        let err = parse2::<FieldAttribute>(input).err().unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Expected `rename = ..` or `sensitive` in output attribute"
+        );
+    }
+
+    #[test]
+    fn test_field_attribute_parses_sensitive_and_force_new_correctly() {
+        let input = quote! {
+            #[input(optional, sensitive, force_new)]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(result.kind, FieldKind::Input(InputKind::Optional)));
+        assert!(result.sensitive && result.force_new);
+
+        let input = quote! {
+            #[output(sensitive)]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(result.sensitive && !result.force_new);
+    }
+
+    #[test]
+    fn test_field_attribute_parses_rename_correctly() {
+        let input = quote! {
+            #[input(default = 3, rename = "replicaCount")]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(
+            result.kind,
+            FieldKind::Input(InputKind::Default(_))
+        ));
+        assert_eq!(result.rename.unwrap().value(), "replicaCount");
+
+        let input = quote! {
+            #[output(rename = "resourceId")]
+        };
+
+        let result: FieldAttribute = parse2(input).unwrap();
+
+        assert!(matches!(result.kind, FieldKind::Output));
+        assert_eq!(result.rename.unwrap().value(), "resourceId");
+    }
 }
@@ -4,22 +4,35 @@ use quote::quote;
 use syn::{spanned::Spanned, Ident, ItemMod};
 
 use crate::{
-    attribute::Attribute, provider_definition::ProviderDef, provider_implementation::ProviderImpl,
-    resource_definition::ResourceDef, resource_implementation::ResourceImpl,
+    attribute::Attribute, fault_provider::expand_fault_provider_impl,
+    layered_provider::expand_layered_provider_impl, mock_provider::expand_mock_provider_impl,
+    provider_args::ProviderArgs, provider_config::ProviderConfig, provider_definition::ProviderDef,
+    provider_implementation::ProviderImpl, record_provider::expand_record_provider_impl,
+    recorded_resource::expand_recorded_resource, resource_definition::ResourceDef,
+    resource_implementation::ResourceImpl, rest_provider::expand_rest_provider_impl,
 };
 
 pub(crate) struct Definition {
     ident: Ident,
+    args: ProviderArgs,
     resource_defs: Vec<ResourceDef>,
     resource_impls: Vec<ResourceImpl>,
-    provider_def: ProviderDef,
-    provider_impl: ProviderImpl,
+    provider_def: Option<ProviderDef>,
+    provider_impl: Option<ProviderImpl>,
+    provider_config: Option<ProviderConfig>,
 }
 
 impl Definition {
     pub(crate) fn expand(self) -> proc_macro2::TokenStream {
         let mod_name = self.ident;
 
+        let recorded_resource = expand_recorded_resource(
+            &self
+                .resource_defs
+                .iter()
+                .map(|r| r.ident())
+                .collect::<Vec<&Ident>>(),
+        );
         let resource_def = self
             .resource_defs
             .into_iter()
@@ -27,18 +40,45 @@ impl Definition {
         let resource_impl = self.resource_impls.iter().map(|r_impl| r_impl.expand());
         let resource_trait = ResourceDef::expand_resource_trait();
 
-        let provider_def = self.provider_def.expand_provider_struct();
-        let provider_impl = self.provider_impl.expand();
-        let provider_trait = ProviderDef::expand_provider_trait();
+        let provider_def = self
+            .provider_def
+            .map(|provider_def| provider_def.expand_provider_struct());
+        let provider_impl = self
+            .provider_impl
+            .map(|provider_impl| provider_impl.expand());
+        let rest_provider_impl = self.args.rest().then(expand_rest_provider_impl);
+        let mock_provider_impl = self.args.mock().then(expand_mock_provider_impl);
+        let record_provider_impl = expand_record_provider_impl();
+        let fault_provider_impl = expand_fault_provider_impl();
+        let layered_provider_impl = expand_layered_provider_impl();
+        let provider_trait = ProviderDef::expand_provider_trait(
+            self.args.expand_name(&mod_name),
+            self.args.expand_version(),
+        );
+        let async_provider_trait = ProviderDef::expand_async_provider_trait(
+            self.args.expand_name(&mod_name),
+            self.args.expand_version(),
+        );
+        let provider_config = self
+            .provider_config
+            .map(|config| config.expand_config_struct(&mod_name));
 
         quote! {
             pub mod #mod_name {
                 pub mod prelude {
+                    #provider_config
                     #provider_def
                     #provider_trait
+                    #async_provider_trait
                     #provider_impl
+                    #rest_provider_impl
+                    #mock_provider_impl
+                    #record_provider_impl
+                    #fault_provider_impl
+                    #layered_provider_impl
 
                     #resource_trait
+                    #recorded_resource
                     #(#resource_def)*
                     #(#resource_impl)*
                 }
@@ -47,10 +87,8 @@ impl Definition {
     }
 }
 
-impl TryFrom<ItemMod> for Definition {
-    type Error = syn::Error;
-
-    fn try_from(value: ItemMod) -> Result<Self, Self::Error> {
+impl Definition {
+    pub(crate) fn try_from(value: ItemMod, args: ProviderArgs) -> syn::Result<Self> {
         let item_span = value.span();
         let ident = value.ident;
 
@@ -66,13 +104,18 @@ impl TryFrom<ItemMod> for Definition {
         let mut resource_impls: Vec<ResourceImpl> = Vec::new();
         let mut provider_def: Option<ProviderDef> = None;
         let mut provider_impl: Option<ProviderImpl> = None;
+        let mut provider_config: Option<ProviderConfig> = None;
 
         for item in items {
             let provider_attribute: Option<Attribute> = get_item_attribute(&item)?;
+            let span = item.span();
 
             match provider_attribute {
-                Some(Attribute::ResourceDefinition { outputs }) => {
-                    let resrouce_def = ResourceDef::try_from(item, outputs)?;
+                Some(Attribute::ResourceDefinition {
+                    outputs,
+                    derive_resource,
+                }) => {
+                    let resrouce_def = ResourceDef::try_from(item, outputs, derive_resource)?;
                     resource_defs.push(resrouce_def);
                 }
                 Some(Attribute::ResourceImplementation) => {
@@ -80,35 +123,316 @@ impl TryFrom<ItemMod> for Definition {
                     resource_impls.push(resource_impl);
                 }
                 Some(Attribute::ProviderDefintion) => {
-                    provider_def = Some(ProviderDef::try_from(item)?)
+                    set_once(&mut provider_def, ProviderDef::try_from(item)?, span)?
                 }
                 Some(Attribute::ProviderImplementation) => {
-                    provider_impl = Some(ProviderImpl::try_from(item)?)
+                    set_once(&mut provider_impl, ProviderImpl::try_from(item)?, span)?
+                }
+                Some(Attribute::ProviderConfig) => {
+                    set_once(&mut provider_config, ProviderConfig::try_from(item)?, span)?
                 }
                 None => {}
             }
         }
 
-        let Some(provider_def) = provider_def else {
+        // A module may leave out its own provider, e.g. to only be used with the REST provider,
+        // but a definition is useless without an implementation and vice versa.
+        if provider_def.is_some() && provider_impl.is_none() {
             return Err(syn::Error::new(
                 Span::call_site(),
-                "Provider definition not specified",
+                "Provider implementation not specified",
             ));
-        };
+        }
 
-        let Some(provider_impl) = provider_impl else {
+        if provider_impl.is_some() && provider_def.is_none() {
             return Err(syn::Error::new(
                 Span::call_site(),
-                "Provider implementation not specified",
+                "Provider definition not specified",
             ));
-        };
+        }
+
+        Self::check_resource_pairs(&resource_defs, &resource_impls)?;
 
         Ok(Self {
             ident,
+            args,
             resource_defs,
             resource_impls,
             provider_def,
             provider_impl,
+            provider_config,
+        })
+    }
+
+    /// Every resource definition that does not derive `Resource` needs exactly one implementation,
+    /// and every implementation needs a definition in this module.
+    fn check_resource_pairs(
+        resource_defs: &[ResourceDef],
+        resource_impls: &[ResourceImpl],
+    ) -> syn::Result<()> {
+        let mut errors: Vec<syn::Error> = Vec::new();
+
+        for resource_def in resource_defs {
+            let ident = resource_def.ident();
+            let impls = resource_impls
+                .iter()
+                .filter(|r| r.self_ident() == Some(ident))
+                .collect::<Vec<&ResourceImpl>>();
+
+            if resource_def.derives_resource() {
+                if let Some(resource_impl) = impls.first() {
+                    errors.push(syn::Error::new(
+                        resource_impl.span(),
+                        format!(
+                            "Resource `{}` derives `Resource` and cannot also have a `#[resource_implementation]`",
+                            ident
+                        ),
+                    ));
+                }
+                continue;
+            }
+
+            if impls.is_empty() {
+                errors.push(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "Resource `{}` has no matching `#[resource_implementation]`",
+                        ident
+                    ),
+                ));
+            }
+
+            for duplicate in impls.iter().skip(1) {
+                errors.push(syn::Error::new(
+                    duplicate.span(),
+                    format!(
+                        "Resource `{}` has more than one `#[resource_implementation]`",
+                        ident
+                    ),
+                ));
+            }
+        }
+
+        for resource_impl in resource_impls {
+            let defined = resource_impl
+                .self_ident()
+                .is_some_and(|ident| resource_defs.iter().any(|r| r.ident() == ident));
+
+            if !defined {
+                let name = resource_impl
+                    .self_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+
+                errors.push(syn::Error::new(
+                    resource_impl.span(),
+                    format!(
+                        "`#[resource_implementation]` for `{}` has no matching `#[resource_definition]`",
+                        name
+                    ),
+                ));
+            }
+        }
+
+        errors
+            .into_iter()
+            .reduce(|mut combined, err| {
+                combined.combine(err);
+                combined
+            })
+            .map_or(Ok(()), Err)
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, span: Span) -> syn::Result<()> {
+    if slot.replace(value).is_some() {
+        return Err(syn::Error::new(
+            span,
+            "Duplicate provider item, only one is allowed per provider",
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quote::quote;
+    use syn::parse2;
+
+    fn definition(tokens: proc_macro2::TokenStream) -> syn::Result<Definition> {
+        Definition::try_from(parse2::<ItemMod>(tokens).unwrap(), ProviderArgs::default())
+    }
+
+    #[test]
+    fn accepts_matching_resource_pairs() {
+        let result = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+
+                #[resource_definition]
+                struct Foo {}
+
+                #[resource_implementation]
+                impl Resource for Foo {}
+            }
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn returns_error_for_resource_without_implementation() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+
+                #[resource_definition]
+                struct Foo {}
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Resource `Foo` has no matching `#[resource_implementation]`"
+        );
+    }
+
+    #[test]
+    fn returns_error_for_implementation_without_resource() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+
+                #[resource_implementation]
+                impl Resource for Bar {}
+            }
         })
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "`#[resource_implementation]` for `Bar` has no matching `#[resource_definition]`"
+        );
+    }
+
+    #[test]
+    fn returns_error_for_duplicate_resource_implementation() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+
+                #[resource_definition]
+                struct Foo {}
+
+                #[resource_implementation]
+                impl Resource for Foo {}
+
+                #[resource_implementation]
+                impl Resource for Foo {}
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Resource `Foo` has more than one `#[resource_implementation]`"
+        );
+    }
+
+    #[test]
+    fn returns_error_for_derived_resource_with_implementation() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+
+                #[resource_definition(derive(Resource))]
+                struct Foo {}
+
+                #[resource_implementation]
+                impl Resource for Foo {}
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Resource `Foo` derives `Resource` and cannot also have a `#[resource_implementation]`"
+        );
+    }
+
+    #[test]
+    fn accepts_module_without_provider() {
+        let result = definition(quote! {
+            mod test_provider {
+                #[resource_definition(derive(Resource))]
+                struct Foo {}
+            }
+        });
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn returns_error_for_provider_definition_without_implementation() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(err.to_string(), "Provider implementation not specified");
+    }
+
+    #[test]
+    fn returns_error_for_duplicate_provider_definition() {
+        let err = definition(quote! {
+            mod test_provider {
+                #[provider_definition]
+                struct TestProvider;
+
+                #[provider_definition]
+                struct OtherProvider;
+
+                #[provider_implementation]
+                impl Provider for TestProvider {}
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Duplicate provider item, only one is allowed per provider"
+        );
     }
 }
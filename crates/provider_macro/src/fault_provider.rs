@@ -0,0 +1,114 @@
+use quote::quote;
+
+/// Implements the module's `Provider` and `AsyncProvider` traits for `::runtime::FaultInjector`,
+/// wrapping any provider of the module. The injector reports the wrapped provider's name, version
+/// and config.
+pub(crate) fn expand_fault_provider_impl() -> proc_macro2::TokenStream {
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let resource_trait_name = helpers::resource_trait_name();
+
+    quote! {
+        impl<P: #provider_trait_name> #provider_trait_name for ::runtime::FaultInjector<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = P::Config;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                P::configure(config).map(Self::new)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner().retry_policy()
+            }
+
+            fn get<R: #resource_trait_name>(&self, resource: &R) -> R::Payload {
+                self.inner().get(resource)
+            }
+
+            fn create<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject(ctx, |inner| inner.create(ctx, resource))
+            }
+
+            fn read<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject(ctx, |inner| inner.read(ctx, resource))
+            }
+
+            fn update<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject(ctx, |inner| inner.update(ctx, resource))
+            }
+
+            fn delete<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject(ctx, |inner| inner.delete(ctx, resource))
+            }
+        }
+
+        impl<P: #async_provider_trait_name + Sync> #async_provider_trait_name for ::runtime::FaultInjector<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = P::Config;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                P::configure(config).map(Self::new)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner().retry_policy()
+            }
+
+            async fn get<R: #resource_trait_name + Sync>(&self, resource: &R) -> R::Payload {
+                self.inner().get(resource).await
+            }
+
+            async fn create<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject_async(ctx, move |inner| inner.create(ctx, resource)).await
+            }
+
+            async fn read<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject_async(ctx, move |inner| inner.read(ctx, resource)).await
+            }
+
+            async fn update<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject_async(ctx, move |inner| inner.update(ctx, resource)).await
+            }
+
+            async fn delete<R: #resource_trait_name + Sync>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.inject_async(ctx, move |inner| inner.delete(ctx, resource)).await
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use quote::quote;
+
+/// Implements the module's `Provider` and `AsyncProvider` traits for `::runtime::Layered`, running
+/// every operation of the wrapped provider through its layers. Outputs returned by a layer, e.g.
+/// from a cache, are applied to the resource.
+pub(crate) fn expand_layered_provider_impl() -> proc_macro2::TokenStream {
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let resource_trait_name = helpers::resource_trait_name();
+
+    quote! {
+        impl<P: #provider_trait_name> #provider_trait_name for ::runtime::Layered<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = P::Config;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                P::configure(config).map(Self::new)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner().retry_policy()
+            }
+
+            fn get<R: #resource_trait_name>(&self, resource: &R) -> R::Payload {
+                self.inner().get(resource)
+            }
+
+            fn create<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self.call(ctx, &inputs, &mut |inner| {
+                    inner.create(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn read<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self.call(ctx, &inputs, &mut |inner| {
+                    inner.read(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn update<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self.call(ctx, &inputs, &mut |inner| {
+                    inner.update(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn delete<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.call(ctx, &resource.inputs()?, &mut |inner| {
+                    inner.delete(ctx, resource)?;
+                    Ok(::runtime::Values::new())
+                })?;
+                Ok(())
+            }
+        }
+
+        impl<P: #async_provider_trait_name + Sync> #async_provider_trait_name for ::runtime::Layered<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = P::Config;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                P::configure(config).map(Self::new)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner().retry_policy()
+            }
+
+            async fn get<R: #resource_trait_name + Sync>(&self, resource: &R) -> R::Payload {
+                self.inner().get(resource).await
+            }
+
+            async fn create<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self
+                    .call_async(ctx, &inputs, resource, |inner, ctx, resource| {
+                        Box::pin(async move {
+                            inner.create(ctx, resource).await?;
+                            Ok(resource.outputs()?)
+                        })
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn read<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self
+                    .call_async(ctx, &inputs, resource, |inner, ctx, resource| {
+                        Box::pin(async move {
+                            inner.read(ctx, resource).await?;
+                            Ok(resource.outputs()?)
+                        })
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn update<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let outputs = self
+                    .call_async(ctx, &inputs, resource, |inner, ctx, resource| {
+                        Box::pin(async move {
+                            inner.update(ctx, resource).await?;
+                            Ok(resource.outputs()?)
+                        })
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn delete<R: #resource_trait_name + Sync>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                self.call_async(ctx, &inputs, &mut &*resource, |inner, ctx, resource| {
+                    Box::pin(async move {
+                        inner.delete(ctx, *resource).await?;
+                        Ok(::runtime::Values::new())
+                    })
+                })
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
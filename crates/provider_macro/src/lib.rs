@@ -1,18 +1,33 @@
 mod attribute;
 mod definition;
+mod fault_provider;
+mod layered_provider;
+mod mock_provider;
+mod provider_args;
+mod provider_config;
 mod provider_definition;
 mod provider_implementation;
+mod record_provider;
+mod recorded_resource;
 mod resource_definition;
 mod resource_implementation;
+mod rest_provider;
 
-use crate::definition::Definition;
+use crate::{definition::Definition, provider_args::ProviderArgs};
 use syn::{parse2, ItemMod};
 
 #[proc_macro_attribute]
 pub fn provider(
-    _attrs: proc_macro::TokenStream,
+    attrs: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let args = match parse2::<ProviderArgs>(attrs.into()) {
+        Ok(args) => args,
+        Err(err) => {
+            return err.to_compile_error().into();
+        }
+    };
+
     let item_mod = match parse2::<ItemMod>(input.into()) {
         Ok(item_mod) => item_mod,
         Err(err) => {
@@ -20,7 +35,7 @@ pub fn provider(
         }
     };
 
-    let def = match Definition::try_from(item_mod) {
+    let def = match Definition::try_from(item_mod, args) {
         Ok(def) => def,
         Err(err) => return err.to_compile_error().into(),
     };
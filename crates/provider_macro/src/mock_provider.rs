@@ -0,0 +1,101 @@
+use quote::quote;
+
+/// Implements the module's `Provider` and `AsyncProvider` traits for `::runtime::MockProvider`,
+/// enabled with `#[provider(mock)]`. Outputs are computed by `set_outputs` unless the mock is
+/// configured to return others.
+pub(crate) fn expand_mock_provider_impl() -> proc_macro2::TokenStream {
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let resource_trait_name = helpers::resource_trait_name();
+
+    quote! {
+        impl #provider_trait_name for ::runtime::MockProvider {
+            type Config = ();
+
+            fn configure(_config: ()) -> Result<Self, ::runtime::ConfigError> {
+                Ok(Self::new())
+            }
+
+            fn create<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                resource.set_outputs();
+                let outputs = self.handle(ctx, resource.inputs()?, resource.outputs()?)?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn read<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.handle(ctx, resource.inputs()?, resource.outputs()?)?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn update<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                resource.set_outputs();
+                let outputs = self.handle(ctx, resource.inputs()?, resource.outputs()?)?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn delete<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.handle(ctx, resource.inputs()?, resource.outputs()?)?;
+                Ok(())
+            }
+        }
+
+        impl #async_provider_trait_name for ::runtime::MockProvider {
+            type Config = ();
+
+            fn configure(_config: ()) -> Result<Self, ::runtime::ConfigError> {
+                Ok(Self::new())
+            }
+
+            async fn create<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                <Self as #provider_trait_name>::create(self, ctx, resource)
+            }
+
+            async fn read<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                <Self as #provider_trait_name>::read(self, ctx, resource)
+            }
+
+            async fn update<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                <Self as #provider_trait_name>::update(self, ctx, resource)
+            }
+
+            async fn delete<R: #resource_trait_name + Sync>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                <Self as #provider_trait_name>::delete(self, ctx, resource)
+            }
+        }
+    }
+}
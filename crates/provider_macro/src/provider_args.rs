@@ -0,0 +1,226 @@
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Ident, LitStr, Token,
+};
+
+/// Arguments of the `#[provider(..)]` attribute itself.
+#[derive(Default)]
+pub(crate) struct ProviderArgs {
+    name: Option<LitStr>,
+    version: Option<LitStr>,
+    mock: bool,
+    rest: bool,
+}
+
+impl ProviderArgs {
+    /// Name defaults to the provider module name.
+    pub(crate) fn expand_name(&self, mod_name: &Ident) -> proc_macro2::TokenStream {
+        match &self.name {
+            Some(name) => quote! { #name },
+            None => {
+                let name = LitStr::new(&mod_name.to_string(), mod_name.span());
+                quote! { #name }
+            }
+        }
+    }
+
+    /// Version defaults to the version of the crate defining the provider.
+    pub(crate) fn expand_version(&self) -> proc_macro2::TokenStream {
+        match &self.version {
+            Some(version) => quote! { #version },
+            None => quote! { env!("CARGO_PKG_VERSION") },
+        }
+    }
+
+    /// Whether to implement the provider trait for `::runtime::MockProvider`.
+    pub(crate) fn mock(&self) -> bool {
+        self.mock
+    }
+
+    /// Whether to implement the provider trait for `::runtime::rest::RestProvider`.
+    pub(crate) fn rest(&self) -> bool {
+        self.rest
+    }
+}
+
+impl Parse for ProviderArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        while !input.is_empty() {
+            let key = input.parse::<Ident>()?;
+
+            let flag = if key == "mock" {
+                Some(&mut args.mock)
+            } else if key == "rest" {
+                Some(&mut args.rest)
+            } else {
+                None
+            };
+            if let Some(flag) = flag {
+                if *flag {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("Duplicate provider argument `{}`", key),
+                    ));
+                }
+
+                *flag = true;
+
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+                continue;
+            }
+
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?;
+
+            let slot = if key == "name" {
+                validate_name(&value)?;
+                &mut args.name
+            } else if key == "version" {
+                validate_version(&value)?;
+                &mut args.version
+            } else {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("Unknown provider argument `{}`", key),
+                ));
+            };
+
+            if slot.replace(value).is_some() {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("Duplicate provider argument `{}`", key),
+                ));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+fn validate_name(name: &LitStr) -> syn::Result<()> {
+    let value = name.value();
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            name.span(),
+            "Provider name must be non-empty and only contain lowercase letters, digits, `_` or `-`",
+        ))
+    }
+}
+
+fn validate_version(version: &LitStr) -> syn::Result<()> {
+    let value = version.value();
+    let core = value.split(['-', '+']).next().unwrap_or_default();
+    let parts = core.split('.').collect::<Vec<&str>>();
+    let valid = parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            version.span(),
+            "Provider version must be a semantic version like `1.4.0`",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quote::quote;
+    use syn::parse2;
+
+    #[test]
+    fn parses_empty_args() {
+        let args: ProviderArgs = parse2(quote! {}).unwrap();
+
+        assert!(args.name.is_none());
+        assert!(args.version.is_none());
+    }
+
+    #[test]
+    fn parses_name_and_version() {
+        let args: ProviderArgs = parse2(quote! { name = "acme", version = "1.4.0" }).unwrap();
+
+        assert_eq!(args.name.unwrap().value(), "acme");
+        assert_eq!(args.version.unwrap().value(), "1.4.0");
+    }
+
+    #[test]
+    fn parses_mock_flag() {
+        let args: ProviderArgs = parse2(quote! { name = "acme", mock }).unwrap();
+
+        assert!(args.mock());
+        assert!(!args.rest());
+        assert!(parse2::<ProviderArgs>(quote! { mock, mock }).is_err());
+    }
+
+    #[test]
+    fn parses_rest_flag() {
+        let args: ProviderArgs = parse2(quote! { rest, mock }).unwrap();
+
+        assert!(args.rest());
+        assert!(args.mock());
+        assert_eq!(
+            parse2::<ProviderArgs>(quote! { rest, rest })
+                .err()
+                .unwrap()
+                .to_string(),
+            "Duplicate provider argument `rest`"
+        );
+    }
+
+    #[test]
+    fn returns_error_for_invalid_version() {
+        let err = parse2::<ProviderArgs>(quote! { version = "1.4" })
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err.to_string(),
+            "Provider version must be a semantic version like `1.4.0`"
+        );
+    }
+
+    #[test]
+    fn returns_error_for_invalid_name() {
+        let err = parse2::<ProviderArgs>(quote! { name = "Acme Cloud" })
+            .err()
+            .unwrap();
+
+        assert!(err
+            .to_string()
+            .starts_with("Provider name must be non-empty"));
+    }
+
+    #[test]
+    fn returns_error_for_unknown_and_duplicate_args() {
+        let err = parse2::<ProviderArgs>(quote! { region = "eu" })
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Unknown provider argument `region`");
+
+        let err = parse2::<ProviderArgs>(quote! { name = "a", name = "b" })
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Duplicate provider argument `name`");
+    }
+}
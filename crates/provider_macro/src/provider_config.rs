@@ -0,0 +1,98 @@
+use quote::{quote, ToTokens};
+use syn::{
+    spanned::Spanned, token::Pub, GenericArgument, Ident, Item, ItemStruct, LitStr, PathArguments,
+    Type, Visibility,
+};
+
+pub(crate) struct ProviderConfig {
+    item_struct: ItemStruct,
+}
+
+impl ProviderConfig {
+    pub(crate) fn try_from(item: Item) -> syn::Result<Self> {
+        let span = item.span();
+        let mut item_struct = if let Item::Struct(item) = item {
+            item
+        } else {
+            return Err(syn::Error::new(
+                item.span(),
+                "Invalid provider config, expected struct item",
+            ));
+        };
+
+        if item_struct.fields.iter().any(|f| f.ident.is_none()) {
+            return Err(syn::Error::new(
+                item_struct.fields.span(),
+                "Invalid provider config, expected named fields",
+            ));
+        }
+
+        item_struct.attrs = vec![];
+        item_struct.vis = Visibility::Public(Pub(span));
+        item_struct
+            .fields
+            .iter_mut()
+            .for_each(|f| f.vis = Visibility::Public(Pub(span)));
+
+        Ok(Self { item_struct })
+    }
+
+    /// Expands the config struct along with constructors reading it from the environment, using
+    /// `<MOD_NAME>_<FIELD>` variables, or from a file of `field = value` lines.
+    pub(crate) fn expand_config_struct(&self, mod_name: &Ident) -> proc_macro2::TokenStream {
+        let item_struct_name = &self.item_struct.ident;
+        let item_struct = self.item_struct.to_token_stream();
+        let env_prefix = LitStr::new(&mod_name.to_string().to_uppercase(), mod_name.span());
+
+        let field_value = self.item_struct.fields.iter().filter_map(|f| {
+            let ident = f.ident.as_ref()?;
+            let key = LitStr::new(&ident.to_string(), ident.span());
+
+            if is_option(&f.ty) {
+                Some(quote! { #ident: source.get_optional(#key)? })
+            } else {
+                Some(quote! { #ident: source.get(#key)? })
+            }
+        });
+
+        quote! {
+            #[allow(dead_code)]
+            #item_struct
+
+            impl #item_struct_name {
+                pub fn from_env() -> Result<Self, ::runtime::ConfigError> {
+                    Self::from_source(&::runtime::ConfigSource::from_env(#env_prefix))
+                }
+
+                pub fn from_file(
+                    path: impl AsRef<::std::path::Path>,
+                ) -> Result<Self, ::runtime::ConfigError> {
+                    Self::from_source(&::runtime::ConfigSource::from_file(path)?)
+                }
+
+                pub fn from_source(
+                    source: &::runtime::ConfigSource,
+                ) -> Result<Self, ::runtime::ConfigError> {
+                    Ok(Self {
+                        #(#field_value,)*
+                    })
+                }
+            }
+        }
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+
+    type_path.path.segments.last().is_some_and(|segment| {
+        segment.ident == "Option"
+            && matches!(
+                &segment.arguments,
+                PathArguments::AngleBracketed(args)
+                    if matches!(args.args.first(), Some(GenericArgument::Type(_)))
+            )
+    })
+}
@@ -36,15 +36,155 @@ impl ProviderDef {
         }
     }
 
-    pub(crate) fn expand_provider_trait() -> proc_macro2::TokenStream {
+    pub(crate) fn expand_provider_trait(
+        name: proc_macro2::TokenStream,
+        version: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
         let provider_trait_name = helpers::provider_trait_name();
         let resource_trait_name = helpers::resource_trait_name();
 
         quote! {
+            #[allow(unused_variables)]
             pub trait #provider_trait_name {
-                const url: &'static str;
+                const NAME: &'static str = #name;
+                const VERSION: &'static str = #version;
 
-                fn get<R: #resource_trait_name>(&self, resource: &R) -> R::Payload;
+                type Config;
+
+                fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError>
+                where
+                    Self: Sized;
+
+                /// Retries for operations that fail with a retryable error, unless the resource
+                /// declares its own policy.
+                fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                    ::runtime::RetryPolicy::default()
+                }
+
+                /// The payload of `resource` as the provider has it, which by default is the one
+                /// the resource declares.
+                fn get<R: #resource_trait_name>(&self, resource: &R) -> R::Payload {
+                    resource.payload()
+                }
+
+                /// Creates `resource` and fills in its outputs.
+                fn create<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> Result<(), ::runtime::ProviderError> {
+                    resource.set_outputs();
+                    Ok(())
+                }
+
+                /// Refreshes the outputs of an existing `resource`, which hold the last recorded
+                /// values when called.
+                fn read<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> Result<(), ::runtime::ProviderError> {
+                    Ok(())
+                }
+
+                /// Applies changed inputs to an existing `resource`.
+                fn update<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> Result<(), ::runtime::ProviderError> {
+                    resource.set_outputs();
+                    Ok(())
+                }
+
+                fn delete<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &R,
+                ) -> Result<(), ::runtime::ProviderError> {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// The async counterpart of the `Provider` trait, for providers built on async network
+    /// clients. Its futures are `Send`, so a State can be applied on a multi-threaded executor.
+    pub(crate) fn expand_async_provider_trait(
+        name: proc_macro2::TokenStream,
+        version: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let async_provider_trait_name = helpers::async_provider_trait_name();
+        let resource_trait_name = helpers::resource_trait_name();
+
+        quote! {
+            #[allow(unused_variables)]
+            pub trait #async_provider_trait_name {
+                const NAME: &'static str = #name;
+                const VERSION: &'static str = #version;
+
+                type Config;
+
+                fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError>
+                where
+                    Self: Sized;
+
+                /// Retries for operations that fail with a retryable error, unless the resource
+                /// declares its own policy.
+                fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                    ::runtime::RetryPolicy::default()
+                }
+
+                /// The payload of `resource` as the provider has it, which by default is the one
+                /// the resource declares.
+                fn get<R: #resource_trait_name + Sync>(
+                    &self,
+                    resource: &R,
+                ) -> impl ::std::future::Future<Output = R::Payload> + Send {
+                    async move { resource.payload() }
+                }
+
+                /// Creates `resource` and fills in its outputs.
+                fn create<R: #resource_trait_name + Send>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> impl ::std::future::Future<Output = Result<(), ::runtime::ProviderError>> + Send {
+                    async move {
+                        resource.set_outputs();
+                        Ok(())
+                    }
+                }
+
+                /// Refreshes the outputs of an existing `resource`, which hold the last recorded
+                /// values when called.
+                fn read<R: #resource_trait_name + Send>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> impl ::std::future::Future<Output = Result<(), ::runtime::ProviderError>> + Send {
+                    async move { Ok(()) }
+                }
+
+                /// Applies changed inputs to an existing `resource`.
+                fn update<R: #resource_trait_name + Send>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> impl ::std::future::Future<Output = Result<(), ::runtime::ProviderError>> + Send {
+                    async move {
+                        resource.set_outputs();
+                        Ok(())
+                    }
+                }
+
+                fn delete<R: #resource_trait_name + Sync>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &R,
+                ) -> impl ::std::future::Future<Output = Result<(), ::runtime::ProviderError>> + Send {
+                    async move { Ok(()) }
+                }
             }
         }
     }
@@ -0,0 +1,173 @@
+use quote::quote;
+
+/// Implements the module's `Provider` and `AsyncProvider` traits for `::runtime::Recorder`,
+/// wrapping any provider of the module. The recorder reports the wrapped provider's name and
+/// version.
+pub(crate) fn expand_record_provider_impl() -> proc_macro2::TokenStream {
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let resource_trait_name = helpers::resource_trait_name();
+
+    quote! {
+        impl<P: #provider_trait_name> #provider_trait_name for ::runtime::Recorder<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = ::runtime::RecorderConfig<P::Config>;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                Self::from_config(config, P::configure)
+            }
+
+            // Replaying has no wrapped provider to ask, so recorded retries must follow the
+            // default policy to replay in order.
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner()
+                    .map_or_else(::runtime::RetryPolicy::default, |inner| inner.retry_policy())
+            }
+
+            fn get<R: #resource_trait_name>(&self, resource: &R) -> R::Payload {
+                self.inner()
+                    .map_or_else(|| resource.payload(), |inner| inner.get(resource))
+            }
+
+            fn create<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.intercept(ctx, resource.inputs()?, |inner| {
+                    inner.create(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn read<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.intercept(ctx, resource.inputs()?, |inner| {
+                    inner.read(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn update<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.intercept(ctx, resource.inputs()?, |inner| {
+                    inner.update(ctx, resource)?;
+                    Ok(resource.outputs()?)
+                })?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn delete<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.intercept(ctx, resource.inputs()?, |inner| {
+                    inner.delete(ctx, resource)?;
+                    Ok(::runtime::Values::new())
+                })?;
+                Ok(())
+            }
+        }
+
+        impl<P: #async_provider_trait_name + Sync> #async_provider_trait_name for ::runtime::Recorder<P> {
+            const NAME: &'static str = P::NAME;
+            const VERSION: &'static str = P::VERSION;
+
+            type Config = ::runtime::RecorderConfig<P::Config>;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                Self::from_config(config, P::configure)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.inner()
+                    .map_or_else(::runtime::RetryPolicy::default, |inner| inner.retry_policy())
+            }
+
+            async fn get<R: #resource_trait_name + Sync>(&self, resource: &R) -> R::Payload {
+                match self.inner() {
+                    Some(inner) => inner.get(resource).await,
+                    None => resource.payload(),
+                }
+            }
+
+            async fn create<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let target = &mut *resource;
+                let outputs = self
+                    .intercept_async(ctx, inputs, move |inner| async move {
+                        inner.create(ctx, target).await?;
+                        Ok(target.outputs()?)
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn read<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let target = &mut *resource;
+                let outputs = self
+                    .intercept_async(ctx, inputs, move |inner| async move {
+                        inner.read(ctx, target).await?;
+                        Ok(target.outputs()?)
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn update<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let inputs = resource.inputs()?;
+                let target = &mut *resource;
+                let outputs = self
+                    .intercept_async(ctx, inputs, move |inner| async move {
+                        inner.update(ctx, target).await?;
+                        Ok(target.outputs()?)
+                    })
+                    .await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn delete<R: #resource_trait_name + Sync>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.intercept_async(ctx, resource.inputs()?, move |inner| async move {
+                    inner.delete(ctx, resource).await?;
+                    Ok(::runtime::Values::new())
+                })
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
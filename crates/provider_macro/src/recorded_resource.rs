@@ -0,0 +1,116 @@
+use quote::quote;
+use syn::{Ident, LitStr};
+
+/// An enum of every resource type in the module, restored from a record in a state file, so that
+/// a State can plan and make the delete of a resource that is no longer in its definition.
+pub(crate) fn expand_recorded_resource(resources: &[&Ident]) -> proc_macro2::TokenStream {
+    let recorded_resource_name = helpers::recorded_resource_name();
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let type_name = resources
+        .iter()
+        .map(|ident| LitStr::new(&ident.to_string(), ident.span()))
+        .collect::<Vec<LitStr>>();
+
+    let from_record_body = if cfg!(feature = "serde") {
+        quote! {
+            let mut values = record.inputs.clone();
+            values.extend(record.outputs.clone());
+            let values = ::runtime::Value::Object(values.into_iter().collect());
+
+            let resource = match record.resource_type.as_str() {
+                #(#type_name => ::runtime::serde_json::from_value(values).map(Self::#resources),)*
+                other => return Err(::runtime::AttributeError {
+                    field: String::from("type"),
+                    reason: format!("`{}` is not a resource type of this provider", other),
+                }),
+            };
+
+            resource.map_err(|err| ::runtime::AttributeError {
+                field: format!("{}.{}", record.resource_type, record.name),
+                reason: err.to_string(),
+            })
+        }
+    } else {
+        quote! {
+            Err(::runtime::AttributeError {
+                field: format!("{}.{}", record.resource_type, record.name),
+                reason: String::from("restoring a resource needs provider_macro's `serde` feature"),
+            })
+        }
+    };
+
+    quote! {
+        /// A resource of any type in this module, restored from its record in a state file, such
+        /// as one removed from a `state!` definition that is still to be deleted.
+        pub enum #recorded_resource_name {
+            #(#resources(#resources),)*
+        }
+
+        impl #recorded_resource_name {
+            pub fn from_record(record: &::runtime::ResourceRecord) -> Result<Self, ::runtime::AttributeError> {
+                #from_record_body
+            }
+
+            /// Whether `resource_type` is the type of a resource in this module.
+            pub fn has_type(resource_type: &str) -> bool {
+                [#(#type_name),*].contains(&resource_type)
+            }
+
+            pub fn type_name(&self) -> &'static str {
+                match *self {
+                    #(Self::#resources(_) => <#resources as ::runtime::Attributes>::TYPE_NAME,)*
+                }
+            }
+
+            /// The planned delete of the resource recorded as `record`.
+            pub fn planned_delete(
+                &self,
+                record: &::runtime::ResourceRecord,
+            ) -> Result<::runtime::PlannedChange, ::runtime::AttributeError> {
+                match *self {
+                    #(
+                        Self::#resources(ref resource) => ::runtime::PlannedChange::for_resource(
+                            &record.name,
+                            resource,
+                            Some(record),
+                            ::runtime::Action::Delete,
+                        ),
+                    )*
+                }
+            }
+
+            pub fn retry_policy(&self) -> Option<::runtime::RetryPolicy> {
+                match *self {
+                    #(Self::#resources(ref resource) => resource.retry_policy(),)*
+                }
+            }
+
+            pub fn timeouts(&self) -> ::runtime::Timeouts {
+                match *self {
+                    #(Self::#resources(ref resource) => resource.timeouts(),)*
+                }
+            }
+
+            pub fn delete<P: #provider_trait_name>(
+                &self,
+                provider: &P,
+                ctx: &::runtime::Context,
+            ) -> Result<(), ::runtime::ProviderError> {
+                match *self {
+                    #(Self::#resources(ref resource) => provider.delete(ctx, resource),)*
+                }
+            }
+
+            pub async fn delete_async<P: #async_provider_trait_name>(
+                &self,
+                provider: &P,
+                ctx: &::runtime::Context,
+            ) -> Result<(), ::runtime::ProviderError> {
+                match *self {
+                    #(Self::#resources(ref resource) => provider.delete(ctx, resource).await,)*
+                }
+            }
+        }
+    }
+}
@@ -1,17 +1,45 @@
+use std::collections::HashSet;
+
 use quote::{format_ident, quote, ToTokens};
 use syn::{
-    spanned::Spanned, token::Pub, Field, Fields, Item, ItemStruct, Pat, PatIdent, PatType, Token,
-    Visibility,
+    parse2, parse_quote, spanned::Spanned, token::Pub, Expr, Field, Fields, Ident, Item,
+    ItemStruct, LitStr, PatType, Type, Visibility,
 };
 
+use crate::attribute::{FieldAttribute, FieldKind, InputKind};
+
 const OUTPUT_IDENTIFIER: &str = "__output_";
 
 pub(crate) struct ResourceDef {
     item_struct: ItemStruct,
+    inputs: Vec<InputField>,
+    outputs: Vec<OutputField>,
+    derive_resource: bool,
+}
+
+struct InputField {
+    ident: Ident,
+    ty: Type,
+    kind: InputKind,
+    wire_name: LitStr,
+    sensitive: bool,
+    force_new: bool,
+}
+
+struct OutputField {
+    ident: Ident,
+    name: Ident,
+    ty: Type,
+    wire_name: LitStr,
+    sensitive: bool,
 }
 
 impl ResourceDef {
-    pub(crate) fn try_from(item: Item, mut outputs: Vec<PatType>) -> syn::Result<Self> {
+    pub(crate) fn try_from(
+        item: Item,
+        outputs: Vec<PatType>,
+        derive_resource: bool,
+    ) -> syn::Result<Self> {
         let span = item.span();
         let mut item_struct = if let Item::Struct(item) = item {
             item
@@ -22,142 +50,582 @@ impl ResourceDef {
             ));
         };
 
+        let mut input_fields: Vec<InputField> = Vec::new();
+        let mut output_fields: Vec<OutputField> = Vec::new();
+
         if let Fields::Named(ref mut named_fields) = item_struct.fields {
-            outputs.iter_mut().for_each(|p| {
-                let ident = format_ident!(
-                    "{}{}",
-                    OUTPUT_IDENTIFIER,
-                    p.pat.as_ref().to_token_stream().to_string()
-                );
-
-                let field = Field {
+            for field in named_fields.named.iter_mut() {
+                let Some(ident) = field.ident.clone() else {
+                    continue;
+                };
+
+                let FieldAttribute {
+                    kind,
+                    rename,
+                    sensitive,
+                    force_new,
+                } = Self::take_field_attribute(field)?.unwrap_or(FieldAttribute {
+                    kind: FieldKind::Input(InputKind::Required),
+                    rename: None,
+                    sensitive: false,
+                    force_new: false,
+                });
+                let wire_name =
+                    rename.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+
+                match kind {
+                    FieldKind::Output => {
+                        Self::add_serde_field_attributes(
+                            field,
+                            &wire_name,
+                            Some(quote! { default }),
+                        );
+                        output_fields.push(OutputField {
+                            name: ident.clone(),
+                            ident,
+                            ty: field.ty.clone(),
+                            wire_name,
+                            sensitive,
+                        })
+                    }
+                    FieldKind::Input(kind) => {
+                        // A missing input takes the value `new` would give it.
+                        let default = match kind {
+                            InputKind::Required => None,
+                            InputKind::Optional => Some(quote! { default }),
+                            InputKind::Default(_) => {
+                                let path = LitStr::new(
+                                    &format!("{}::{}", item_struct.ident, default_fn_name(&ident)),
+                                    ident.span(),
+                                );
+                                Some(quote! { default = #path })
+                            }
+                        };
+                        Self::add_serde_field_attributes(field, &wire_name, default);
+                        input_fields.push(InputField {
+                            ident,
+                            ty: field.ty.clone(),
+                            kind,
+                            wire_name,
+                            sensitive,
+                            force_new,
+                        })
+                    }
+                }
+            }
+
+            for p in outputs {
+                let name = format_ident!("{}", p.pat.as_ref().to_token_stream().to_string());
+                let ident = format_ident!("{}{}", OUTPUT_IDENTIFIER, name);
+                let wire_name = LitStr::new(&name.to_string(), p.pat.span());
+
+                let mut field = Field {
                     attrs: Vec::new(),
                     vis: Visibility::Inherited,
                     mutability: syn::FieldMutability::None,
-                    ident: Some(ident),
+                    ident: Some(ident.clone()),
                     colon_token: Some(p.colon_token),
                     ty: *p.ty.clone(),
                 };
+                Self::add_serde_field_attributes(&mut field, &wire_name, Some(quote! { default }));
 
-                named_fields.named.push_value(field);
-            });
+                named_fields.named.push(field);
+                output_fields.push(OutputField {
+                    ident,
+                    name,
+                    ty: *p.ty,
+                    wire_name,
+                    sensitive: false,
+                });
+            }
         }
 
         item_struct.attrs = vec![];
         item_struct.vis = Visibility::Public(Pub(span));
 
-        Ok(Self { item_struct })
+        if cfg!(feature = "serde") {
+            Self::check_wire_names(&input_fields, &output_fields)?;
+            item_struct.attrs.push(parse_quote! {
+                #[derive(::runtime::serde::Serialize, ::runtime::serde::Deserialize)]
+            });
+            item_struct.attrs.push(parse_quote! {
+                #[serde(crate = "::runtime::serde")]
+            });
+        }
+
+        Ok(Self {
+            item_struct,
+            inputs: input_fields,
+            outputs: output_fields,
+            derive_resource,
+        })
+    }
+
+    pub(crate) fn ident(&self) -> &Ident {
+        &self.item_struct.ident
+    }
+
+    pub(crate) fn derives_resource(&self) -> bool {
+        self.derive_resource
+    }
+
+    /// Renames the serialized field to `wire_name`, and gives it `default`, e.g. `default` or
+    /// `default = "path"`, for when it is missing.
+    fn add_serde_field_attributes(
+        field: &mut Field,
+        wire_name: &LitStr,
+        default: Option<proc_macro2::TokenStream>,
+    ) {
+        if !cfg!(feature = "serde") {
+            return;
+        }
+
+        field
+            .attrs
+            .push(parse_quote! { #[serde(rename = #wire_name)] });
+        if let Some(default) = default {
+            field.attrs.push(parse_quote! { #[serde(#default)] });
+        }
+    }
+
+    /// Inputs and outputs share one namespace once serialized, so an output declared through
+    /// `outputs = {..}` with the same name as an input needs a different name.
+    fn check_wire_names(inputs: &[InputField], outputs: &[OutputField]) -> syn::Result<()> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let wire_names = inputs
+            .iter()
+            .map(|f| &f.wire_name)
+            .chain(outputs.iter().map(|f| &f.wire_name));
+
+        for wire_name in wire_names {
+            if !seen.insert(wire_name.value()) {
+                return Err(syn::Error::new(
+                    wire_name.span(),
+                    format!(
+                        "Serialized field name `{}` is used more than once, use `rename = ..` to disambiguate",
+                        wire_name.value()
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn take_field_attribute(field: &mut Field) -> syn::Result<Option<FieldAttribute>> {
+        let (field_attrs, attrs): (Vec<syn::Attribute>, Vec<syn::Attribute>) = field
+            .attrs
+            .drain(..)
+            .partition(FieldAttribute::is_field_attribute);
+        field.attrs = attrs;
+
+        let mut field_attrs = field_attrs.into_iter();
+        let field_attribute = field_attrs
+            .next()
+            .map(|attr| parse2::<FieldAttribute>(attr.into_token_stream()))
+            .transpose()?;
+
+        if let Some(attr) = field_attrs.next() {
+            return Err(syn::Error::new(
+                attr.span(),
+                "Field can only have a single `input` or `output` attribute",
+            ));
+        }
+
+        Ok(field_attribute)
     }
 
     pub(crate) fn expand_resource_struct(self) -> proc_macro2::TokenStream {
         let item_struct_name = self.item_struct.ident.to_token_stream();
         let item_struct = self.item_struct.to_token_stream();
 
-        let (output_field, non_output_field): (Vec<Field>, Vec<Field>) =
-            self.item_struct.fields.into_iter().partition(|f| {
-                if let Some(ident) = &f.ident {
-                    ident.to_string().starts_with(OUTPUT_IDENTIFIER)
-                } else {
-                    false
-                }
-            });
-
-        let new_fn = Self::expand_new_method(&output_field, &non_output_field);
-        let getter_fns = Self::expand_getters(&output_field);
-        let setter_fns = Self::expand_setters(&output_field);
+        let default_fns = self.expand_default_fns();
+        let new_fn = self.expand_new_method();
+        let builder_fn = self.expand_builder_method();
+        let required_inputs_fn = self.expand_required_inputs_assertion();
+        let getter_fns = self.expand_getters();
+        let setter_fns = self.expand_setters();
+        let builder = self.expand_builder();
+        let derived_resource_impl = self
+            .derive_resource
+            .then(|| self.expand_derived_resource_impl());
+        let attributes_impl = self.expand_attributes_impl();
 
         quote! {
             #[allow(dead_code)]
             #item_struct
 
             impl #item_struct_name {
+                #default_fns
                 #new_fn
+                #builder_fn
+                #required_inputs_fn
                 #getter_fns
                 #setter_fns
             }
+
+            #builder
+            #derived_resource_impl
+            #attributes_impl
         }
     }
 
-    fn expand_new_method(
-        output_field: &Vec<Field>,
-        non_output_field: &Vec<Field>,
-    ) -> proc_macro2::TokenStream {
-        let output_field_name = output_field.iter().filter_map(|f| f.ident.clone());
-        let non_output_field_name = non_output_field.iter().filter_map(|f| f.ident.clone());
+    fn expand_attributes_impl(&self) -> proc_macro2::TokenStream {
+        let struct_name = &self.item_struct.ident;
+        let type_name = LitStr::new(&struct_name.to_string(), struct_name.span());
 
-        let non_output_pat = non_output_field.iter().filter_map(|f| {
-            if let Some(ident) = f.ident.clone() {
-                let pat_ident = PatIdent {
-                    attrs: Vec::new(),
-                    by_ref: None,
-                    mutability: None,
-                    ident,
-                    subpat: None,
-                };
+        let sensitive_field = self
+            .inputs
+            .iter()
+            .filter(|f| f.sensitive)
+            .map(|f| &f.wire_name)
+            .chain(
+                self.outputs
+                    .iter()
+                    .filter(|f| f.sensitive)
+                    .map(|f| &f.wire_name),
+            );
+        let force_new_field = self
+            .inputs
+            .iter()
+            .filter(|f| f.force_new)
+            .map(|f| &f.wire_name);
 
-                Some(PatType {
-                    attrs: Vec::new(),
-                    pat: Box::new(Pat::Ident(pat_ident)),
-                    colon_token: Token![:](f.ty.span()),
-                    ty: Box::new(f.ty.clone()),
+        let has_values = cfg!(feature = "serde");
+        let (inputs_body, outputs_body, set_inputs_body, set_outputs_body) = if cfg!(
+            feature = "serde"
+        ) {
+            let input_name = self
+                .inputs
+                .iter()
+                .map(|f| &f.ident)
+                .collect::<Vec<&Ident>>();
+            let input_wire_name = self
+                .inputs
+                .iter()
+                .map(|f| &f.wire_name)
+                .collect::<Vec<&LitStr>>();
+            let output_name = self
+                .outputs
+                .iter()
+                .map(|f| &f.ident)
+                .collect::<Vec<&Ident>>();
+            let output_wire_name = self
+                .outputs
+                .iter()
+                .map(|f| &f.wire_name)
+                .collect::<Vec<&LitStr>>();
+
+            (
+                quote! {
+                    Ok(::runtime::Values::from([
+                        #((String::from(#input_wire_name), ::runtime::to_value(#input_wire_name, &self.#input_name)?),)*
+                    ]))
+                },
+                quote! {
+                    Ok(::runtime::Values::from([
+                        #((String::from(#output_wire_name), ::runtime::to_value(#output_wire_name, &self.#output_name)?),)*
+                    ]))
+                },
+                quote! {
+                    #(
+                        if let Some(value) = values.get(#input_wire_name) {
+                            self.#input_name = ::runtime::serde_json::from_value(value.clone())
+                                .map_err(|err| ::runtime::AttributeError {
+                                    field: String::from(#input_wire_name),
+                                    reason: err.to_string(),
+                                })?;
+                        }
+                    )*
+                    Ok(())
+                },
+                quote! {
+                    #(
+                        if let Some(value) = values.get(#output_wire_name) {
+                            self.#output_name = ::runtime::serde_json::from_value(value.clone())
+                                .map_err(|err| ::runtime::AttributeError {
+                                    field: String::from(#output_wire_name),
+                                    reason: err.to_string(),
+                                })?;
+                        }
+                    )*
+                    Ok(())
+                },
+            )
+        } else {
+            (
+                quote! { Ok(::runtime::Values::new()) },
+                quote! { Ok(::runtime::Values::new()) },
+                quote! { Ok(()) },
+                quote! { Ok(()) },
+            )
+        };
+
+        quote! {
+            impl ::runtime::Attributes for #struct_name {
+                const TYPE_NAME: &'static str = #type_name;
+                const SENSITIVE_FIELDS: &'static [&'static str] = &[#(#sensitive_field),*];
+                const FORCE_NEW_FIELDS: &'static [&'static str] = &[#(#force_new_field),*];
+                const HAS_VALUES: bool = #has_values;
+
+                fn inputs(&self) -> Result<::runtime::Values, ::runtime::AttributeError> {
+                    #inputs_body
+                }
+
+                fn outputs(&self) -> Result<::runtime::Values, ::runtime::AttributeError> {
+                    #outputs_body
+                }
+
+                #[allow(unused_variables)]
+                fn set_input_values(
+                    &mut self,
+                    values: &::runtime::Values,
+                ) -> Result<(), ::runtime::AttributeError> {
+                    #set_inputs_body
+                }
+
+                #[allow(unused_variables)]
+                fn set_output_values(
+                    &mut self,
+                    values: &::runtime::Values,
+                ) -> Result<(), ::runtime::AttributeError> {
+                    #set_outputs_body
+                }
+            }
+        }
+    }
+
+    /// Implements `Resource` with a payload struct holding a copy of every input field.
+    fn expand_derived_resource_impl(&self) -> proc_macro2::TokenStream {
+        let resource_trait_name = helpers::resource_trait_name();
+        let struct_name = &self.item_struct.ident;
+        let payload_name = format_ident!("{}Payload", struct_name);
+        let input_name = self
+            .inputs
+            .iter()
+            .map(|f| &f.ident)
+            .collect::<Vec<&Ident>>();
+        let input_type = self.inputs.iter().map(|f| &f.ty);
+        let serde_attrs = cfg!(feature = "serde").then(|| {
+            quote! {
+                #[derive(::runtime::serde::Serialize, ::runtime::serde::Deserialize)]
+                #[serde(crate = "::runtime::serde")]
+            }
+        });
+        let field_serde_attrs = self.inputs.iter().map(|f| {
+            let wire_name = &f.wire_name;
+            cfg!(feature = "serde").then(|| quote! { #[serde(rename = #wire_name)] })
+        });
+
+        quote! {
+            #[derive(Clone)]
+            #serde_attrs
+            pub struct #payload_name {
+                #(#field_serde_attrs pub #input_name: #input_type,)*
+            }
+
+            impl #resource_trait_name for #struct_name {
+                type Payload = #payload_name;
+
+                fn payload(&self) -> Self::Payload {
+                    #payload_name {
+                        #(#input_name: self.#input_name.clone(),)*
+                    }
+                }
+            }
+        }
+    }
+
+    fn builder_name(&self) -> Ident {
+        format_ident!("{}Builder", self.item_struct.ident)
+    }
+
+    /// A function per `#[input(default = ..)]` returning the default, which `new`, the builder
+    /// and deserialization share.
+    fn expand_default_fns(&self) -> proc_macro2::TokenStream {
+        let (default_fn, (default_type, default_expr)): (Vec<Ident>, (Vec<&Type>, Vec<&Expr>)) =
+            self.inputs
+                .iter()
+                .filter_map(|f| match &f.kind {
+                    InputKind::Default(expr) => Some((default_fn_name(&f.ident), (&f.ty, expr))),
+                    _ => None,
                 })
-            } else {
-                None
+                .unzip();
+
+        quote! {
+            #(
+                #[doc(hidden)]
+                pub fn #default_fn() -> #default_type {
+                    #default_expr
+                }
+            )*
+        }
+    }
+
+    fn expand_new_method(&self) -> proc_macro2::TokenStream {
+        let (required_name, required_type): (Vec<&Ident>, Vec<&Type>) = self
+            .inputs
+            .iter()
+            .filter(|f| matches!(f.kind, InputKind::Required))
+            .map(|f| (&f.ident, &f.ty))
+            .unzip();
+
+        let defaulted_field = self.inputs.iter().filter_map(|f| {
+            let ident = &f.ident;
+            match &f.kind {
+                InputKind::Required => None,
+                InputKind::Optional => Some(quote! { #ident: Default::default() }),
+                InputKind::Default(_) => {
+                    let default_fn = default_fn_name(ident);
+                    Some(quote! { #ident: Self::#default_fn() })
+                }
             }
         });
+        let output_field_name = self.outputs.iter().map(|f| &f.ident);
 
         quote! {
             pub fn new(
-                #(#non_output_pat)*
+                #(#required_name: #required_type,)*
             ) -> Self {
                 Self {
-                    #(#non_output_field_name,)*
+                    #(#required_name,)*
+                    #(#defaulted_field,)*
                     #(#output_field_name: Default::default(),)*
                 }
             }
         }
     }
 
-    fn expand_getters(output_field: &Vec<Field>) -> proc_macro2::TokenStream {
-        let output_field_name = output_field.iter().filter_map(|f| f.ident.clone());
-        let output_field_type = output_field.iter().map(|f| f.ty.clone());
+    fn expand_builder_method(&self) -> proc_macro2::TokenStream {
+        let builder_name = self.builder_name();
+
+        quote! {
+            pub fn builder() -> #builder_name {
+                #builder_name::default()
+            }
+        }
+    }
 
-        let getter_name = output_field_name
-            .clone()
-            .filter_map(|i| {
-                let ident = i.to_string();
-                ident
-                    .split(OUTPUT_IDENTIFIER)
-                    .filter(|s| !s.is_empty())
-                    .next()
-                    .map(|s| s.to_string())
+    /// Const fn called by `state!` so that a resource missing one of its required inputs fails
+    /// to compile instead of panicking in `build`.
+    fn expand_required_inputs_assertion(&self) -> proc_macro2::TokenStream {
+        let struct_name = &self.item_struct.ident;
+        let (required_name, missing_msg): (Vec<LitStr>, Vec<LitStr>) = self
+            .inputs
+            .iter()
+            .filter(|f| matches!(f.kind, InputKind::Required))
+            .map(|f| {
+                let msg = format!("`{}` is missing required input `{}`", struct_name, f.ident);
+                (
+                    LitStr::new(&f.ident.to_string(), f.ident.span()),
+                    LitStr::new(&msg, f.ident.span()),
+                )
             })
-            .map(|i| format_ident!("get_{}", i));
+            .unzip();
+
+        quote! {
+            #[doc(hidden)]
+            pub const fn __assert_required_inputs(provided: &[&str]) {
+                #[allow(dead_code)]
+                const fn contains(provided: &[&str], name: &str) -> bool {
+                    let name = name.as_bytes();
+                    let mut i = 0;
+                    while i < provided.len() {
+                        let candidate = provided[i].as_bytes();
+                        if candidate.len() == name.len() {
+                            let mut j = 0;
+                            while j < name.len() && candidate[j] == name[j] {
+                                j += 1;
+                            }
+                            if j == name.len() {
+                                return true;
+                            }
+                        }
+                        i += 1;
+                    }
+                    false
+                }
+
+                #(
+                    if !contains(provided, #required_name) {
+                        panic!(#missing_msg);
+                    }
+                )*
+            }
+        }
+    }
+
+    fn expand_builder(&self) -> proc_macro2::TokenStream {
+        let struct_name = &self.item_struct.ident;
+        let builder_name = self.builder_name();
+        let input_name = self
+            .inputs
+            .iter()
+            .map(|f| &f.ident)
+            .collect::<Vec<&Ident>>();
+        let input_type = self.inputs.iter().map(|f| &f.ty).collect::<Vec<&Type>>();
+
+        let input_value = self.inputs.iter().map(|f| {
+            let ident = &f.ident;
+            match &f.kind {
+                InputKind::Required => {
+                    let msg = format!("`{}` is missing required input `{}`", struct_name, ident);
+                    quote! { self.#ident.expect(#msg) }
+                }
+                InputKind::Optional => quote! { self.#ident.unwrap_or_default() },
+                InputKind::Default(_) => {
+                    let default_fn = default_fn_name(ident);
+                    quote! { self.#ident.unwrap_or_else(#struct_name::#default_fn) }
+                }
+            }
+        });
+        let output_field_name = self.outputs.iter().map(|f| &f.ident);
+
+        quote! {
+            #[derive(Default)]
+            pub struct #builder_name {
+                #(#input_name: Option<#input_type>,)*
+            }
+
+            impl #builder_name {
+                #(
+                    pub fn #input_name(mut self, val: #input_type) -> Self {
+                        self.#input_name = Some(val);
+                        self
+                    }
+                )*
+
+                /// # Panics
+                ///
+                /// Panics if a required input has not been set.
+                pub fn build(self) -> #struct_name {
+                    #struct_name {
+                        #(#input_name: #input_value,)*
+                        #(#output_field_name: Default::default(),)*
+                    }
+                }
+            }
+        }
+    }
+
+    fn expand_getters(&self) -> proc_macro2::TokenStream {
+        let output_field_name = self.outputs.iter().map(|f| &f.ident);
+        let output_field_type = self.outputs.iter().map(|f| &f.ty);
+        let getter_name = self.outputs.iter().map(|f| format_ident!("get_{}", f.name));
 
         quote! {
             #(
                 pub fn #getter_name(&self) -> #output_field_type {
-                    self.#output_field_name
+                    self.#output_field_name.clone()
                 }
             )*
         }
     }
 
-    fn expand_setters(output_field: &Vec<Field>) -> proc_macro2::TokenStream {
-        let output_field_name = output_field.iter().filter_map(|f| f.ident.clone());
-        let output_field_type = output_field.iter().map(|f| f.ty.clone());
-
-        let setter_name = output_field_name
-            .clone()
-            .filter_map(|i| {
-                let ident = i.to_string();
-                ident
-                    .split(OUTPUT_IDENTIFIER)
-                    .filter(|s| !s.is_empty())
-                    .next()
-                    .map(|s| s.to_string())
-            })
-            .map(|i| format_ident!("set_{}", i));
+    fn expand_setters(&self) -> proc_macro2::TokenStream {
+        let output_field_name = self.outputs.iter().map(|f| &f.ident);
+        let output_field_type = self.outputs.iter().map(|f| &f.ty);
+        let setter_name = self.outputs.iter().map(|f| format_ident!("set_{}", f.name));
 
         quote! {
             #(
@@ -170,14 +638,33 @@ impl ResourceDef {
 
     pub(crate) fn expand_resource_trait() -> proc_macro2::TokenStream {
         let resource_trait_name = helpers::resource_trait_name();
+        let payload_bound =
+            cfg!(feature = "serde").then(|| quote! { : ::runtime::serde::Serialize });
+
         quote! {
-            pub trait #resource_trait_name {
-                type Payload;
+            pub trait #resource_trait_name: ::runtime::Attributes {
+                type Payload #payload_bound;
 
                 fn payload(&self) -> Self::Payload;
 
                 fn set_outputs(&mut self) {}
+
+                /// Overrides the provider's retry policy for operations on this resource.
+                fn retry_policy(&self) -> Option<::runtime::RetryPolicy> {
+                    None
+                }
+
+                /// Default operation timeouts for this resource type, which `state!` may
+                /// override per resource.
+                fn timeouts(&self) -> ::runtime::Timeouts {
+                    ::runtime::Timeouts::default()
+                }
             }
         }
     }
 }
+
+/// The function returning the default of the input `field`.
+fn default_fn_name(field: &Ident) -> Ident {
+    format_ident!("__default_{}", field)
+}
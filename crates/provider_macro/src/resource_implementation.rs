@@ -1,5 +1,5 @@
 use quote::{quote, ToTokens};
-use syn::{spanned::Spanned, Item, ItemImpl};
+use syn::{spanned::Spanned, Ident, Item, ItemImpl, Type};
 
 pub(crate) struct ResourceImpl {
     item_impl: ItemImpl,
@@ -21,6 +21,18 @@ impl ResourceImpl {
         Ok(Self { item_impl })
     }
 
+    /// Name of the struct the implementation is for.
+    pub(crate) fn self_ident(&self) -> Option<&Ident> {
+        match self.item_impl.self_ty.as_ref() {
+            Type::Path(type_path) => type_path.path.segments.last().map(|s| &s.ident),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn span(&self) -> proc_macro2::Span {
+        self.item_impl.self_ty.span()
+    }
+
     pub(crate) fn expand(&self) -> proc_macro2::TokenStream {
         let item_impl = self.item_impl.to_token_stream();
 
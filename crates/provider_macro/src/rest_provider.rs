@@ -0,0 +1,125 @@
+use quote::quote;
+
+/// Implements the module's `Provider` and `AsyncProvider` traits for
+/// `::runtime::rest::RestProvider`, enabled with `#[provider(rest)]`. Request bodies are serialized
+/// payloads, so this needs the `serde` feature. Async operations run the blocking requests on the
+/// runtime's worker threads.
+pub(crate) fn expand_rest_provider_impl() -> proc_macro2::TokenStream {
+    if !cfg!(feature = "serde") {
+        return quote! {
+            compile_error!("`#[provider(rest)]` needs provider_macro's `serde` feature");
+        };
+    }
+
+    let provider_trait_name = helpers::provider_trait_name();
+    let async_provider_trait_name = helpers::async_provider_trait_name();
+    let resource_trait_name = helpers::resource_trait_name();
+
+    quote! {
+        impl #provider_trait_name for ::runtime::rest::RestProvider {
+            type Config = ::runtime::rest::RestConfig;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                Self::new(config)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.config().retry_policy.clone()
+            }
+
+            fn create<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let body = ::runtime::to_value("payload", &resource.payload())?;
+                let outputs = self.send(ctx, &resource.values()?, Some(&body))?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn read<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.send(ctx, &resource.values()?, None)?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn update<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let body = ::runtime::to_value("payload", &resource.payload())?;
+                let outputs = self.send(ctx, &resource.values()?, Some(&body))?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            fn delete<R: #resource_trait_name>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.send(ctx, &resource.values()?, None)?;
+                Ok(())
+            }
+        }
+
+        impl #async_provider_trait_name for ::runtime::rest::RestProvider {
+            type Config = ::runtime::rest::RestConfig;
+
+            fn configure(config: Self::Config) -> Result<Self, ::runtime::ConfigError> {
+                Self::new(config)
+            }
+
+            fn retry_policy(&self) -> ::runtime::RetryPolicy {
+                self.config().retry_policy.clone()
+            }
+
+            async fn create<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let body = ::runtime::to_value("payload", &resource.payload())?;
+                let outputs = self.send_async(ctx, resource.values()?, Some(body)).await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn read<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let outputs = self.send_async(ctx, resource.values()?, None).await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn update<R: #resource_trait_name + Send>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &mut R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                let body = ::runtime::to_value("payload", &resource.payload())?;
+                let outputs = self.send_async(ctx, resource.values()?, Some(body)).await?;
+                resource.set_output_values(&outputs)?;
+                Ok(())
+            }
+
+            async fn delete<R: #resource_trait_name + Sync>(
+                &self,
+                ctx: &::runtime::Context,
+                resource: &R,
+            ) -> Result<(), ::runtime::ProviderError> {
+                self.send_async(ctx, resource.values()?, None).await?;
+                Ok(())
+            }
+        }
+    }
+}
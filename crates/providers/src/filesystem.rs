@@ -0,0 +1,356 @@
+//! Manages files, directories and symlinks on the local filesystem.
+//!
+//! This is also the reference for writing a provider: the generated `Provider` methods are
+//! generic over every resource of the module, so the implementation dispatches on
+//! `ctx.resource_type` and works with the resource's serialized input and output values.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use provider_macro::provider;
+use runtime::{ProviderError, Value, Values};
+use sha2::{Digest, Sha256};
+
+#[provider(name = "local")]
+mod local {
+
+    // Relative resource paths are resolved against `root`, which defaults to the working
+    // directory. Read from `LOCAL_ROOT` by `Config::from_env`.
+    #[provider_config]
+    struct Config {
+        root: Option<String>,
+    }
+
+    #[provider_definition]
+    struct LocalProvider {
+        root: std::path::PathBuf,
+    }
+
+    #[provider_implementation]
+    impl Provider for LocalProvider {
+        type Config = Config;
+
+        fn configure(config: Config) -> Result<Self, runtime::ConfigError> {
+            let root = match config.root {
+                Some(root) => std::path::PathBuf::from(root),
+                None => std::env::current_dir()
+                    .map_err(|err| runtime::ConfigError::invalid("root", err.to_string()))?,
+            };
+
+            Ok(Self { root })
+        }
+
+        fn create<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &mut R,
+        ) -> Result<(), runtime::ProviderError> {
+            let outputs =
+                crate::filesystem::create(&self.root, ctx.resource_type, &resource.values()?)?;
+            resource.set_output_values(&outputs)?;
+            Ok(())
+        }
+
+        fn read<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &mut R,
+        ) -> Result<(), runtime::ProviderError> {
+            let outputs = crate::filesystem::read(ctx.resource_type, &resource.values()?)?;
+            resource.set_output_values(&outputs)?;
+            Ok(())
+        }
+
+        fn update<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &mut R,
+        ) -> Result<(), runtime::ProviderError> {
+            let outputs =
+                crate::filesystem::update(&self.root, ctx.resource_type, &resource.values()?)?;
+            resource.set_output_values(&outputs)?;
+            Ok(())
+        }
+
+        fn delete<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &R,
+        ) -> Result<(), runtime::ProviderError> {
+            crate::filesystem::delete(ctx.resource_type, &resource.values()?)
+        }
+    }
+
+    // A regular file. `mode` sets its Unix permission bits, e.g. `Some(0o600)`, and otherwise it
+    // has the permissions of a new file. Content and permissions changed on disk are restored.
+    #[resource_definition(derive(Resource))]
+    struct File {
+        path: String,
+        content: String,
+        #[input(optional)]
+        mode: Option<u32>,
+        #[output]
+        absolute_path: String,
+        #[output]
+        content_hash: String,
+    }
+
+    #[resource_definition(derive(Resource))]
+    struct Directory {
+        path: String,
+        #[input(optional)]
+        mode: Option<u32>,
+        #[output]
+        absolute_path: String,
+    }
+
+    // A symbolic link at `path` pointing to `target`, which is not resolved against the root.
+    #[resource_definition(derive(Resource))]
+    struct Symlink {
+        path: String,
+        target: String,
+        #[output]
+        absolute_path: String,
+    }
+}
+
+/// Creates the resource described by `values`, replacing anything already at its path, and
+/// returns its outputs.
+fn create(root: &Path, resource_type: &str, values: &Values) -> Result<Values, ProviderError> {
+    let path = root.join(string(values, "path")?);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| io_error("create", parent, err))?;
+    }
+
+    let mut outputs = Values::new();
+
+    match resource_type {
+        "File" => {
+            let content = string(values, "content")?;
+            fs::write(&path, &content).map_err(|err| io_error("write", &path, err))?;
+            outputs.insert(
+                String::from("content_hash"),
+                content_hash(content.as_bytes()),
+            );
+        }
+        "Directory" => {
+            fs::create_dir_all(&path).map_err(|err| io_error("create", &path, err))?;
+        }
+        "Symlink" => {
+            let target = string(values, "target")?;
+            remove_path(&path)?;
+            symlink(Path::new(&target), &path).map_err(|err| io_error("link", &path, err))?;
+        }
+        _ => return Err(unknown_type(resource_type)),
+    }
+
+    if resource_type != "Symlink" {
+        set_mode(&path, mode(values), resource_type == "Directory")?;
+    }
+
+    let absolute_path =
+        std::path::absolute(&path).map_err(|err| io_error("resolve", &path, err))?;
+    outputs.insert(
+        String::from("absolute_path"),
+        Value::String(absolute_path.to_string_lossy().into_owned()),
+    );
+
+    Ok(outputs)
+}
+
+/// Recreates the resource in place, removing the previous one first if its path changed.
+fn update(root: &Path, resource_type: &str, values: &Values) -> Result<Values, ProviderError> {
+    let path = root.join(string(values, "path")?);
+    let absolute_path =
+        std::path::absolute(&path).map_err(|err| io_error("resolve", &path, err))?;
+
+    if values.get("absolute_path").and_then(Value::as_str) != absolute_path.to_str() {
+        delete(resource_type, values)?;
+    }
+
+    create(root, resource_type, values)
+}
+
+/// Refreshes the outputs of an existing resource from disk, and restores the content, mode or
+/// link target declared by its inputs if they were changed outside of it.
+fn read(resource_type: &str, values: &Values) -> Result<Values, ProviderError> {
+    let path = PathBuf::from(string(values, "absolute_path")?);
+    let mut outputs = Values::new();
+
+    match resource_type {
+        "File" => {
+            let declared = string(values, "content")?;
+            let content = fs::read(&path).map_err(|err| io_error("read", &path, err))?;
+            if content != declared.as_bytes() {
+                fs::write(&path, &declared).map_err(|err| io_error("write", &path, err))?;
+            }
+            set_mode(&path, mode(values), false)?;
+            outputs.insert(
+                String::from("content_hash"),
+                content_hash(declared.as_bytes()),
+            );
+        }
+        "Directory" => {
+            fs::symlink_metadata(&path).map_err(|err| io_error("read", &path, err))?;
+            set_mode(&path, mode(values), true)?;
+        }
+        "Symlink" => {
+            let target = string(values, "target")?;
+            let current = fs::read_link(&path).map_err(|err| io_error("read", &path, err))?;
+            if current != Path::new(&target) {
+                remove_path(&path)?;
+                symlink(Path::new(&target), &path).map_err(|err| io_error("link", &path, err))?;
+            }
+        }
+        _ => return Err(unknown_type(resource_type)),
+    }
+
+    Ok(outputs)
+}
+
+/// Removes the resource at its recorded absolute path. Directories are only removed when empty,
+/// so that files managed elsewhere are never deleted with them.
+fn delete(resource_type: &str, values: &Values) -> Result<(), ProviderError> {
+    let path = match values.get("absolute_path") {
+        Some(Value::String(path)) if !path.is_empty() => PathBuf::from(path),
+        _ => return Ok(()),
+    };
+
+    let result = match resource_type {
+        "File" | "Symlink" => return remove_path(&path),
+        "Directory" => fs::remove_dir(&path),
+        _ => return Err(unknown_type(resource_type)),
+    };
+
+    match result {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(io_error("remove", &path, err)),
+        _ => Ok(()),
+    }
+}
+
+fn remove_path(path: &Path) -> Result<(), ProviderError> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(io_error("remove", path, err)),
+        _ => Ok(()),
+    }
+}
+
+fn content_hash(content: &[u8]) -> Value {
+    let hash = Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    Value::String(hash)
+}
+
+fn string(values: &Values, field: &str) -> Result<String, ProviderError> {
+    match values.get(field) {
+        Some(Value::String(value)) => Ok(value.clone()),
+        _ => Err(ProviderError::new(format!(
+            "Expected a string value for `{}`",
+            field
+        ))),
+    }
+}
+
+fn mode(values: &Values) -> Option<u32> {
+    values
+        .get("mode")
+        .and_then(Value::as_u64)
+        .map(|mode| mode as u32)
+}
+
+fn unknown_type(resource_type: &str) -> ProviderError {
+    ProviderError::new(format!("Unknown resource type `{}`", resource_type))
+}
+
+fn io_error(action: &str, path: &Path, err: io::Error) -> ProviderError {
+    ProviderError::new(format!(
+        "Could not {} '{}': {}",
+        action,
+        path.display(),
+        err
+    ))
+}
+
+/// Sets the permission bits of `path` to `mode`, or without one to those of a new file or
+/// directory, so that removing a mode undoes it.
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>, directory: bool) -> Result<(), ProviderError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = mode.unwrap_or_else(|| default_mode(directory));
+    let metadata = fs::metadata(path).map_err(|err| io_error("read", path, err))?;
+    if metadata.permissions().mode() & 0o7777 == mode {
+        return Ok(());
+    }
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|err| io_error("set permissions of", path, err))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, mode: Option<u32>, _directory: bool) -> Result<(), ProviderError> {
+    match mode {
+        Some(_) => Err(ProviderError::new(
+            "File modes are only supported on Unix platforms",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The permission bits of a new file or directory, which are those the process umask leaves.
+#[cfg(unix)]
+fn default_mode(directory: bool) -> u32 {
+    let mode = if directory { 0o777 } else { 0o666 };
+    mode & !umask()
+}
+
+/// The umask as Linux reports it, since setting it to find out would race with other threads.
+/// Elsewhere the common default is assumed.
+#[cfg(unix)]
+fn umask() -> u32 {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Umask:"))
+                .and_then(|umask| u32::from_str_radix(umask.trim(), 8).ok())
+        })
+        .unwrap_or(0o022)
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(target, path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::serde_json::json;
+
+    #[test]
+    fn hashes_content_as_hex_sha256() {
+        assert_eq!(
+            content_hash(b"hello"),
+            json!("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+    }
+
+    #[test]
+    fn returns_error_for_unknown_resource_type() {
+        let values = Values::from([(String::from("path"), json!("a"))]);
+
+        assert!(create(Path::new("."), "Socket", &values).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+//! Providers that ship with the crate and need no external service.
+
+mod filesystem;
+mod utilities;
+
+pub use filesystem::local;
+pub use utilities::utility;
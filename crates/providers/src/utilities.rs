@@ -0,0 +1,186 @@
+//! Random values and null resources, which only exist in state. Generated values are recorded
+//! as outputs and kept until an input changes, so `keepers` and `triggers` decide when a new
+//! value is generated.
+
+use std::collections::BTreeMap;
+
+use provider_macro::provider;
+use runtime::{ProviderError, Value, Values};
+
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const SPECIAL: &[u8] = b"!#$%&*()-_=+[]{}<>:?";
+
+#[provider(name = "utility")]
+mod utility {
+
+    #[provider_definition]
+    struct UtilityProvider;
+
+    #[provider_implementation]
+    impl Provider for UtilityProvider {
+        type Config = ();
+
+        fn configure(_config: ()) -> Result<Self, runtime::ConfigError> {
+            Ok(Self)
+        }
+
+        fn create<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &mut R,
+        ) -> Result<(), runtime::ProviderError> {
+            let outputs = crate::utilities::generate(ctx.resource_type, &resource.values()?)?;
+            resource.set_output_values(&outputs)?;
+            Ok(())
+        }
+
+        fn update<R: Resource>(
+            &self,
+            ctx: &runtime::Context,
+            resource: &mut R,
+        ) -> Result<(), runtime::ProviderError> {
+            let outputs = crate::utilities::generate(ctx.resource_type, &resource.values()?)?;
+            resource.set_output_values(&outputs)?;
+            Ok(())
+        }
+    }
+
+    // `byte_length` random bytes, hex encoded.
+    #[resource_definition(derive(Resource))]
+    struct RandomId {
+        #[input(optional)]
+        keepers: std::collections::BTreeMap<String, String>,
+        #[input(default = 8)]
+        byte_length: u32,
+        #[output]
+        hex: String,
+    }
+
+    #[resource_definition(derive(Resource))]
+    struct RandomPassword {
+        #[input(optional)]
+        keepers: std::collections::BTreeMap<String, String>,
+        #[input(default = 16)]
+        length: u32,
+        #[input(default = true)]
+        special: bool,
+        #[output(sensitive)]
+        result: String,
+    }
+
+    // A random (version 4) UUID.
+    #[resource_definition(derive(Resource))]
+    struct RandomUuid {
+        #[input(optional)]
+        keepers: std::collections::BTreeMap<String, String>,
+        #[output]
+        result: String,
+    }
+
+    // Does nothing but get a new `id` whenever `triggers` change, so that resources depending on
+    // the id are updated along with it.
+    #[resource_definition(derive(Resource))]
+    struct Null {
+        #[input(optional)]
+        triggers: std::collections::BTreeMap<String, String>,
+        #[output]
+        id: String,
+    }
+}
+
+/// Generates new outputs for a resource being created, or updated because its inputs changed.
+fn generate(resource_type: &str, values: &Values) -> Result<Values, ProviderError> {
+    let (field, value) = match resource_type {
+        "RandomId" => {
+            let bytes = random_bytes(number(values, "byte_length")?)?;
+            ("hex", hex(&bytes))
+        }
+        "RandomPassword" => {
+            let mut charset = ALPHANUMERIC.to_vec();
+            if values.get("special").and_then(Value::as_bool) == Some(true) {
+                charset.extend_from_slice(SPECIAL);
+            }
+            ("result", password(number(values, "length")?, &charset)?)
+        }
+        "RandomUuid" => ("result", uuid(random_bytes(16)?)),
+        "Null" => ("id", hex(&random_bytes(8)?)),
+        _ => {
+            return Err(ProviderError::new(format!(
+                "Unknown resource type `{}`",
+                resource_type
+            )))
+        }
+    };
+
+    Ok(BTreeMap::from([(field.to_string(), Value::String(value))]))
+}
+
+fn number(values: &Values, field: &str) -> Result<usize, ProviderError> {
+    values
+        .get(field)
+        .and_then(Value::as_u64)
+        .map(|n| n as usize)
+        .ok_or_else(|| ProviderError::new(format!("Expected a number for `{}`", field)))
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, ProviderError> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|err| ProviderError::new(format!("Could not generate random bytes: {}", err)))?;
+    Ok(bytes)
+}
+
+/// Picks each character by rejection sampling, so every character in `charset` is equally likely.
+fn password(len: usize, charset: &[u8]) -> Result<String, ProviderError> {
+    let limit = 256 - 256 % charset.len();
+    let mut password = String::with_capacity(len);
+
+    while password.len() < len {
+        for b in random_bytes(len - password.len())? {
+            if (b as usize) < limit {
+                password.push(charset[b as usize % charset.len()] as char);
+            }
+        }
+    }
+
+    Ok(password)
+}
+
+fn uuid(mut bytes: Vec<u8>) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formats_version_4_uuid() {
+        let uuid = uuid(vec![0xff; 16]);
+
+        assert_eq!(uuid, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+    }
+
+    #[test]
+    fn generates_password_from_charset() {
+        let password = password(64, b"ab").unwrap();
+
+        assert_eq!(password.len(), 64);
+        assert!(password.chars().all(|c| c == 'a' || c == 'b'));
+    }
+}
@@ -0,0 +1,85 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+pub type Value = serde_json::Value;
+
+/// Field values keyed by their serialized (wire) name.
+pub type Values = BTreeMap<String, Value>;
+
+#[derive(Debug)]
+pub struct AttributeError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid value for field `{}`: {}",
+            self.field, self.reason
+        )
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+/// Implemented for every `#[resource_definition]`. Field values are only available when
+/// provider_macro's `serde` feature is enabled, otherwise they are always empty.
+pub trait Attributes {
+    const TYPE_NAME: &'static str;
+
+    /// Whether field values are available. A State cannot tell changes apart without them, so
+    /// planning, applying or destroying resources without them fails to compile.
+    const HAS_VALUES: bool = true;
+
+    /// Serialized names of the fields marked `sensitive`, whose values are masked when shown.
+    const SENSITIVE_FIELDS: &'static [&'static str] = &[];
+
+    /// Serialized names of the inputs marked `force_new`, which cannot be updated in place, so
+    /// changing one replaces the resource.
+    const FORCE_NEW_FIELDS: &'static [&'static str] = &[];
+
+    fn inputs(&self) -> Result<Values, AttributeError>;
+
+    fn outputs(&self) -> Result<Values, AttributeError>;
+
+    /// Sets the inputs found in `values`, e.g. the recorded ones to delete a resource that is
+    /// being replaced.
+    fn set_input_values(&mut self, values: &Values) -> Result<(), AttributeError>;
+
+    fn set_output_values(&mut self, values: &Values) -> Result<(), AttributeError>;
+
+    /// Inputs and outputs combined, with outputs taking precedence on conflicting names.
+    fn values(&self) -> Result<Values, AttributeError> {
+        let mut values = self.inputs()?;
+        values.extend(self.outputs()?);
+        Ok(values)
+    }
+}
+
+/// Serializes the value of `field`, which fails for values JSON cannot represent, such as maps
+/// with non-string keys.
+pub fn to_value<T: Serialize>(field: &str, value: &T) -> Result<Value, AttributeError> {
+    serde_json::to_value(value).map_err(|err| AttributeError {
+        field: field.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_values_json_cannot_represent() {
+        assert_eq!(to_value("size", &3).unwrap(), serde_json::json!(3));
+
+        let err = to_value("ports", &BTreeMap::from([((1, 2), "a")])).unwrap_err();
+        assert_eq!(err.field, "ports");
+        assert!(err
+            .to_string()
+            .starts_with("Invalid value for field `ports`: key must be"));
+    }
+}
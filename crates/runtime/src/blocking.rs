@@ -0,0 +1,171 @@
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex, MutexGuard, OnceLock},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::DEFAULT_PARALLELISM;
+
+/// How many blocking calls run at once. Matching the default parallelism lets a default apply
+/// keep every resource in progress without waiting for a worker.
+const WORKERS: usize = DEFAULT_PARALLELISM;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of threads for blocking calls made from async code, started on first use, so that
+/// a blocking client neither blocks the executor nor needs a thread per call.
+struct Pool {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl Pool {
+    fn get() -> &'static Self {
+        static POOL: OnceLock<Pool> = OnceLock::new();
+
+        POOL.get_or_init(|| {
+            let (jobs, receiver) = mpsc::channel::<Job>();
+            let receiver = Arc::new(Mutex::new(receiver));
+
+            for index in 0..WORKERS {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("runtime-blocking-{}", index))
+                    .spawn(move || Self::work(&receiver))
+                    .expect("could not start a blocking worker thread");
+            }
+
+            Self { jobs }
+        })
+    }
+
+    fn work(receiver: &Mutex<mpsc::Receiver<Job>>) {
+        loop {
+            let job = lock(receiver).recv();
+            match job {
+                Ok(job) => job(),
+                Err(_) => return,
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+struct Shared<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Runs the blocking `call` on a worker thread and returns a future of its result, on any
+/// executor. A call whose future is dropped still runs to the end, so it should honor the
+/// deadline it is given.
+pub fn offload<T: Send + 'static>(call: impl FnOnce() -> T + Send + 'static) -> Offload<T> {
+    let shared = Arc::new(Mutex::new(Shared {
+        result: None,
+        waker: None,
+    }));
+
+    let done = Arc::clone(&shared);
+    let job: Job = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(call));
+        let mut done = lock(&done);
+        done.result = Some(result);
+        if let Some(waker) = done.waker.take() {
+            waker.wake();
+        }
+    });
+    Pool::get()
+        .jobs
+        .send(job)
+        .expect("the blocking workers run as long as the process");
+
+    Offload { shared }
+}
+
+/// The result of a call given to [`offload`], ready once the worker has finished it.
+pub struct Offload<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Future for Offload<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = lock(&self.shared);
+
+        match shared.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                if !shared
+                    .waker
+                    .as_ref()
+                    .is_some_and(|waker| waker.will_wake(cx.waker()))
+                {
+                    shared.waker = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{sync::Barrier, task::Wake};
+
+    /// Polls `future` on the current thread until it completes.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn runs_calls_on_the_workers_at_once() {
+        // Each call waits for all the others, so they only finish if every worker runs one.
+        let barrier = Arc::new(Barrier::new(WORKERS));
+        let calls = (0..WORKERS)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                offload(move || {
+                    barrier.wait();
+                    thread::current().id()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for call in calls {
+            assert_ne!(block_on(call), thread::current().id());
+        }
+    }
+
+    #[test]
+    fn resumes_a_panic_in_the_awaiting_task() {
+        let result = panic::catch_unwind(|| block_on(offload(|| panic!("lost connection"))));
+
+        assert!(result.is_err());
+        assert_eq!(block_on(offload(|| 1)), 1);
+    }
+}
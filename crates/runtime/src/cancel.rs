@@ -0,0 +1,147 @@
+use std::{
+    fmt,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Cancels an apply or destroy from elsewhere, e.g. a Ctrl-C handler. Clones share the same
+/// cancellation.
+///
+/// Once cancelled, no new operations are started, and operations in progress have until the grace
+/// period ends to finish.
+#[derive(Clone)]
+pub struct CancellationToken {
+    shared: Arc<Shared>,
+    grace_period: Duration,
+}
+
+#[derive(Default)]
+struct Shared {
+    cancelled_at: Mutex<Option<Instant>>,
+    cancelled: Condvar,
+}
+
+impl Default for CancellationToken {
+    /// A token with a grace period of thirty seconds.
+    fn default() -> Self {
+        Self {
+            shared: Arc::default(),
+            grace_period: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = grace_period;
+        self
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+
+    /// Cancels every clone of this token. Cancelling again does not extend the grace period.
+    pub fn cancel(&self) {
+        self.shared
+            .cancelled_at
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_or_insert_with(Instant::now);
+        self.shared.cancelled.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled_at().is_some()
+    }
+
+    /// When operations in progress have to be done by, once cancelled.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.cancelled_at()
+            .map(|cancelled_at| cancelled_at + self.grace_period)
+    }
+
+    /// Blocks until the token is cancelled or `timeout` has passed, and returns whether it was
+    /// cancelled.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let cancelled_at = self
+            .shared
+            .cancelled_at
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let (cancelled_at, _) = self
+            .shared
+            .cancelled
+            .wait_timeout_while(cancelled_at, timeout, |cancelled_at| cancelled_at.is_none())
+            .unwrap_or_else(|err| err.into_inner());
+
+        cancelled_at.is_some()
+    }
+
+    fn cancelled_at(&self) -> Option<Instant> {
+        *self
+            .shared
+            .cancelled_at
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl PartialEq for CancellationToken {
+    /// Tokens are equal when they are clones of each other.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .field("grace_period", &self.grace_period)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn cancels_every_clone() {
+        let token = CancellationToken::new().with_grace_period(Duration::from_secs(5));
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert_eq!(clone.deadline(), None);
+
+        token.cancel();
+        let deadline = clone.deadline().unwrap();
+        assert!(clone.is_cancelled());
+        assert!(deadline > Instant::now() + Duration::from_secs(4));
+
+        token.cancel();
+        assert_eq!(clone.deadline(), Some(deadline));
+        assert_eq!(token, clone);
+        assert_ne!(token, CancellationToken::new());
+    }
+
+    #[test]
+    fn wakes_waiters_when_cancelled() {
+        let token = CancellationToken::new();
+        assert!(!token.wait(Duration::from_millis(1)));
+
+        let clone = token.clone();
+        let waiter = thread::spawn(move || clone.wait(Duration::from_secs(30)));
+        token.cancel();
+
+        assert!(waiter.join().unwrap());
+        assert!(token.wait(Duration::from_secs(30)));
+    }
+}
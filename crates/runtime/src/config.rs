@@ -0,0 +1,208 @@
+use std::{collections::HashMap, env, fmt, fs, path::Path, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Missing { key: String },
+    Invalid { key: String, reason: String },
+    Parse { line: usize, reason: String },
+    Io { path: String, reason: String },
+}
+
+impl ConfigError {
+    pub fn invalid(key: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Invalid {
+            key: key.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { key } => write!(f, "Missing provider config value `{}`", key),
+            Self::Invalid { key, reason } => {
+                write!(f, "Invalid provider config value `{}`: {}", key, reason)
+            }
+            Self::Parse { line, reason } => {
+                write!(
+                    f,
+                    "Invalid provider config file at line {}: {}",
+                    line, reason
+                )
+            }
+            Self::Io { path, reason } => {
+                write!(
+                    f,
+                    "Could not read provider config file '{}': {}",
+                    path, reason
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Raw `key -> value` pairs that a generated provider config is parsed from.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSource {
+    values: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Collects every `<PREFIX>_<KEY>` environment variable, keyed by the lowercased `<KEY>`.
+    pub fn from_env(prefix: &str) -> Self {
+        Self::from_vars(prefix, env::vars())
+    }
+
+    /// Collects `<PREFIX>_<KEY>` variables as `from_env` does, but from `vars` rather than the
+    /// process environment.
+    pub fn from_vars(prefix: &str, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let prefix = format!("{}_", prefix);
+        let values = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(&prefix)
+                    .map(|key| (key.to_lowercase(), value))
+            })
+            .collect();
+
+        Self { values }
+    }
+
+    /// Reads a file of `key = value` lines. Blank lines and lines starting with `#` are skipped.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|err| ConfigError::Io {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        })?;
+
+        contents.parse()
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.get_optional(key)?.ok_or_else(|| ConfigError::Missing {
+            key: key.to_string(),
+        })
+    }
+
+    pub fn get_optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, ConfigError>
+    where
+        T::Err: fmt::Display,
+    {
+        self.values
+            .get(key)
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|err| ConfigError::invalid(key, err.to_string()))
+            })
+            .transpose()
+    }
+}
+
+impl FromStr for ConfigSource {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = HashMap::new();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| ConfigError::Parse {
+                line: idx + 1,
+                reason: String::from("expected `key = value`"),
+            })?;
+
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+
+            values.insert(key.trim().to_string(), value.to_string());
+        }
+
+        Ok(Self { values })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_key_value_lines() {
+        let source: ConfigSource = "# comment\nurl = \"http://localhost\"\n\nretries=3\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            source.get::<String>("url").unwrap(),
+            "http://localhost".to_string()
+        );
+        assert_eq!(source.get::<u32>("retries").unwrap(), 3);
+    }
+
+    #[test]
+    fn collects_prefixed_variables() {
+        let source = ConfigSource::from_vars(
+            "CLOUD",
+            [
+                (String::from("CLOUD_URL"), String::from("http://localhost")),
+                (String::from("OTHER_URL"), String::from("http://other")),
+            ],
+        );
+
+        assert_eq!(source.get::<String>("url").unwrap(), "http://localhost");
+        assert_eq!(source.values.len(), 1);
+    }
+
+    #[test]
+    fn returns_error_for_line_without_separator() {
+        let err = "url = foo\nbar\n".parse::<ConfigSource>().err().unwrap();
+
+        assert_eq!(
+            err,
+            ConfigError::Parse {
+                line: 2,
+                reason: String::from("expected `key = value`")
+            }
+        );
+    }
+
+    #[test]
+    fn returns_error_for_missing_and_invalid_values() {
+        let source = ConfigSource::new().with("retries", "many");
+
+        assert_eq!(
+            source.get::<String>("url").err().unwrap(),
+            ConfigError::Missing {
+                key: String::from("url")
+            }
+        );
+        assert!(matches!(
+            source.get::<u32>("retries").err().unwrap(),
+            ConfigError::Invalid { .. }
+        ));
+        assert_eq!(source.get_optional::<u32>("timeout").unwrap(), None);
+    }
+}
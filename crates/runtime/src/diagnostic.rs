@@ -0,0 +1,46 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub resource: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(resource: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            resource: resource.map(str::to_string),
+            message: message.into(),
+        }
+    }
+
+    pub fn error(resource: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            resource: resource.map(str::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match &self.resource {
+            Some(resource) => write!(f, "{} [{}]: {}", severity, resource, self.message),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
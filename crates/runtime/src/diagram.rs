@@ -0,0 +1,159 @@
+use std::fmt::Write;
+
+use crate::{Action, Graph, Plan};
+
+/// A resource in a `ResourceGraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceNode {
+    pub name: String,
+    pub resource_type: String,
+    /// Names of the resources this one depends on.
+    pub depends_on: Vec<String>,
+    /// What a plan would do to the resource, once set with `ResourceGraph::with_plan`.
+    pub action: Option<Action>,
+}
+
+/// The resources of a State and the dependencies between them, for rendering as a Graphviz DOT or
+/// Mermaid diagram. Edges point from a resource to the resources that depend on it, in the order
+/// they are applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceGraph {
+    pub resources: Vec<ResourceNode>,
+}
+
+impl ResourceGraph {
+    /// Builds the graph from the name and type of each resource by index, and the dependencies
+    /// between those indices.
+    pub fn new(resources: &[(&str, &str)], graph: &Graph) -> Self {
+        let resources = resources
+            .iter()
+            .enumerate()
+            .map(|(index, (name, resource_type))| ResourceNode {
+                name: name.to_string(),
+                resource_type: resource_type.to_string(),
+                depends_on: graph
+                    .dependencies(index)
+                    .iter()
+                    .map(|&dep| resources[dep].0.to_string())
+                    .collect(),
+                action: None,
+            })
+            .collect();
+
+        Self { resources }
+    }
+
+    /// Colors each resource by the action `plan` has for it.
+    pub fn with_plan(mut self, plan: &Plan) -> Self {
+        for node in &mut self.resources {
+            node.action = plan.get(&node.name).map(|change| change.action);
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResourceNode> {
+        self.resources.iter().find(|r| r.name == name)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n    rankdir=LR;\n");
+
+        for node in &self.resources {
+            let _ = write!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{}\"",
+                node.name, node.name, node.resource_type
+            );
+            if let Some(color) = node.action.and_then(fill_color) {
+                let _ = write!(dot, ", style=filled, fillcolor=\"{}\"", color);
+            }
+            dot.push_str("];\n");
+        }
+        for node in &self.resources {
+            for dep in &node.depends_on {
+                let _ = writeln!(dot, "    \"{}\" -> \"{}\";", dep, node.name);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for node in &self.resources {
+            let _ = writeln!(
+                mermaid,
+                "    {}[\"{}<br/>{}\"]",
+                node.name, node.name, node.resource_type
+            );
+        }
+        for node in &self.resources {
+            for dep in &node.depends_on {
+                let _ = writeln!(mermaid, "    {} --> {}", dep, node.name);
+            }
+        }
+        for node in &self.resources {
+            if let Some(color) = node.action.and_then(fill_color) {
+                let _ = writeln!(mermaid, "    style {} fill:{}", node.name, color);
+            }
+        }
+
+        mermaid
+    }
+}
+
+/// Green for resources to be created, yellow for updated, orange for replaced and red for
+/// destroyed. Unchanged resources are left uncolored.
+fn fill_color(action: Action) -> Option<&'static str> {
+    match action {
+        Action::Create => Some("#b7e4c7"),
+        Action::Update => Some("#ffe8a3"),
+        Action::Replace => Some("#ffc89a"),
+        Action::Delete => Some("#f4a6a6"),
+        Action::NoOp => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::PlannedChange;
+
+    fn graph() -> ResourceGraph {
+        ResourceGraph::new(
+            &[("network", "Network"), ("subnet", "Subnet")],
+            &Graph::new(vec![vec![], vec![0]]),
+        )
+    }
+
+    #[test]
+    fn renders_dot_and_mermaid() {
+        let mut plan = Plan::new();
+        plan.push(PlannedChange::new("network", "Network", Action::NoOp));
+        plan.push(PlannedChange::new("subnet", "Subnet", Action::Create));
+        let graph = graph().with_plan(&plan);
+        assert_eq!(graph.get("subnet").unwrap().depends_on, vec!["network"]);
+
+        assert_eq!(
+            graph.to_dot(),
+            r##"digraph {
+    rankdir=LR;
+    "network" [label="network\nNetwork"];
+    "subnet" [label="subnet\nSubnet", style=filled, fillcolor="#b7e4c7"];
+    "network" -> "subnet";
+}
+"##
+        );
+        assert_eq!(
+            graph.to_mermaid(),
+            r##"flowchart LR
+    network["network<br/>Network"]
+    subnet["subnet<br/>Subnet"]
+    network --> subnet
+    style subnet fill:#b7e4c7
+"##
+        );
+    }
+}
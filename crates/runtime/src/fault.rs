@@ -0,0 +1,316 @@
+use std::{future::Future, sync::Mutex, thread, time::Duration};
+
+use crate::{sleep, Context, Operation, ProviderError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FaultKind {
+    /// Fails without calling the wrapped provider.
+    Error(ProviderError),
+    /// Waits for the duration, or until the call's deadline if that is sooner, then fails with a
+    /// timeout without calling the wrapped provider.
+    Timeout(Duration),
+    /// Calls the wrapped provider, then fails even if the call succeeded.
+    AfterSideEffect(ProviderError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Calls {
+    Nth(usize),
+    First(usize),
+    Every,
+}
+
+/// A failure injected into matching calls. By default it matches every operation on every
+/// resource and fires on the first matching call only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fault {
+    operation: Option<Operation>,
+    resource: Option<String>,
+    calls: Calls,
+    kind: FaultKind,
+    matched: usize,
+}
+
+impl Fault {
+    pub fn new(kind: FaultKind) -> Self {
+        Self {
+            operation: None,
+            resource: None,
+            calls: Calls::Nth(1),
+            kind,
+            matched: 0,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(FaultKind::Error(ProviderError::new(message)))
+    }
+
+    pub fn timeout(after: Duration) -> Self {
+        Self::new(FaultKind::Timeout(after))
+    }
+
+    pub fn after_side_effect(message: impl Into<String>) -> Self {
+        Self::new(FaultKind::AfterSideEffect(ProviderError::new(message)))
+    }
+
+    pub fn on(mut self, operation: Operation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    pub fn resource(mut self, name: &str) -> Self {
+        self.resource = Some(name.to_string());
+        self
+    }
+
+    /// Fires on the `n`th matching call only, counting from 1.
+    pub fn nth(mut self, n: usize) -> Self {
+        self.calls = Calls::Nth(n);
+        self
+    }
+
+    /// Fires on the first `n` matching calls.
+    pub fn times(mut self, n: usize) -> Self {
+        self.calls = Calls::First(n);
+        self
+    }
+
+    pub fn always(mut self) -> Self {
+        self.calls = Calls::Every;
+        self
+    }
+
+    /// Counts `ctx` if it matches and returns whether the fault fires for it.
+    fn fires(&mut self, ctx: &Context) -> bool {
+        let matches = self.operation.is_none_or(|op| op == ctx.operation)
+            && self
+                .resource
+                .as_ref()
+                .is_none_or(|name| *name == ctx.resource);
+
+        if !matches {
+            return false;
+        }
+
+        self.matched += 1;
+
+        match self.calls {
+            Calls::Nth(n) => self.matched == n,
+            Calls::First(n) => self.matched <= n,
+            Calls::Every => true,
+        }
+    }
+}
+
+/// A fault that fired, with the call it fired for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InjectedFault {
+    pub operation: Operation,
+    pub resource: String,
+    pub kind: FaultKind,
+}
+
+/// Wraps a provider to fail calls according to a schedule of [`Fault`]s. When several faults
+/// fire for the same call, the first one added wins.
+#[derive(Debug)]
+pub struct FaultInjector<P> {
+    inner: P,
+    faults: Mutex<Vec<Fault>>,
+    injected: Mutex<Vec<InjectedFault>>,
+}
+
+impl<P> FaultInjector<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            faults: Mutex::new(Vec::new()),
+            injected: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_fault(self, fault: Fault) -> Self {
+        self.add_fault(fault);
+        self
+    }
+
+    /// Adds `fault` to the schedule, which also works while the provider is owned by a State.
+    pub fn add_fault(&self, fault: Fault) {
+        self.faults
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(fault);
+    }
+
+    pub fn clear_faults(&self) {
+        self.faults
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        self.injected
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Performs the operation in `ctx` with `call` on the wrapped provider, unless a scheduled
+    /// fault fires for it.
+    pub fn inject<T>(
+        &self,
+        ctx: &Context,
+        call: impl FnOnce(&P) -> Result<T, ProviderError>,
+    ) -> Result<T, ProviderError> {
+        let Some(kind) = self.fire(ctx) else {
+            return call(&self.inner);
+        };
+
+        match kind {
+            FaultKind::Error(err) => Err(err),
+            FaultKind::Timeout(after) => {
+                thread::sleep(timeout_wait(ctx, after));
+                Err(injected_timeout(ctx, after))
+            }
+            FaultKind::AfterSideEffect(err) => {
+                call(&self.inner)?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Like `inject`, but awaits the operation performed by `call`, and an injected timeout.
+    pub async fn inject_async<'a, T, F>(
+        &'a self,
+        ctx: &Context,
+        call: impl FnOnce(&'a P) -> F,
+    ) -> Result<T, ProviderError>
+    where
+        F: Future<Output = Result<T, ProviderError>>,
+    {
+        let Some(kind) = self.fire(ctx) else {
+            return call(&self.inner).await;
+        };
+
+        match kind {
+            FaultKind::Error(err) => Err(err),
+            FaultKind::Timeout(after) => {
+                sleep(timeout_wait(ctx, after)).await;
+                Err(injected_timeout(ctx, after))
+            }
+            FaultKind::AfterSideEffect(err) => {
+                call(&self.inner).await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Counts `ctx` against the schedule and records the fault that fires for it, if any.
+    fn fire(&self, ctx: &Context) -> Option<FaultKind> {
+        let mut kind = None;
+        for fault in self
+            .faults
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter_mut()
+        {
+            // Every matching fault counts the call, even when an earlier one fires for it.
+            if fault.fires(ctx) && kind.is_none() {
+                kind = Some(fault.kind.clone());
+            }
+        }
+
+        let kind = kind?;
+        self.injected
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(InjectedFault {
+                operation: ctx.operation,
+                resource: ctx.resource.clone(),
+                kind: kind.clone(),
+            });
+
+        Some(kind)
+    }
+}
+
+/// How long an injected timeout waits: `after`, or until the call's deadline if that is sooner.
+fn timeout_wait(ctx: &Context, after: Duration) -> Duration {
+    ctx.remaining()
+        .map_or(after, |remaining| remaining.min(after))
+}
+
+fn injected_timeout(ctx: &Context, after: Duration) -> ProviderError {
+    ProviderError::timeout(format!(
+        "Injected timeout: {} of `{}` did not complete within {:?}",
+        ctx.operation, ctx.resource, after
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ctx(name: &str, operation: Operation) -> Context {
+        Context::new(name, "Foo", operation)
+    }
+
+    #[test]
+    fn fires_on_matching_calls_only() {
+        let injector = FaultInjector::new(()).with_fault(
+            Fault::error("boom")
+                .on(Operation::Create)
+                .resource("b")
+                .nth(2),
+        );
+
+        let create_a = ctx("a", Operation::Create);
+        let create_b = ctx("b", Operation::Create);
+
+        assert!(injector.inject(&create_b, |_| Ok(())).is_ok());
+        assert!(injector.inject(&create_a, |_| Ok(())).is_ok());
+        assert!(injector
+            .inject(&ctx("b", Operation::Delete), |_| Ok(()))
+            .is_ok());
+        assert_eq!(
+            injector.inject(&create_b, |_| Ok(())),
+            Err(ProviderError::new("boom"))
+        );
+        assert!(injector.inject(&create_b, |_| Ok(())).is_ok());
+        assert_eq!(injector.injected().len(), 1);
+    }
+
+    #[test]
+    fn fails_after_side_effect() {
+        let injector = FaultInjector::new(()).with_fault(Fault::after_side_effect("lost").times(2));
+        let mut calls = 0;
+
+        for _ in 0..3 {
+            let _ = injector.inject(&ctx("a", Operation::Update), |_| {
+                calls += 1;
+                Ok(())
+            });
+        }
+
+        assert_eq!(calls, 3);
+        assert_eq!(injector.injected().len(), 2);
+    }
+
+    #[test]
+    fn times_out() {
+        let injector =
+            FaultInjector::new(()).with_fault(Fault::timeout(Duration::from_millis(10)).always());
+
+        let err = injector
+            .inject(&ctx("a", Operation::Create), |_| Ok(()))
+            .err()
+            .unwrap();
+
+        assert!(err.message().starts_with("Injected timeout"));
+    }
+}
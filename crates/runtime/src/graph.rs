@@ -0,0 +1,426 @@
+use std::{
+    any::Any,
+    collections::VecDeque,
+    future::{self, Future},
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Condvar, Mutex, MutexGuard},
+    task::Poll,
+    thread,
+};
+
+use crate::CancellationToken;
+
+/// How many operations a State runs at once unless configured otherwise.
+pub const DEFAULT_PARALLELISM: usize = 10;
+
+/// Resources by index, each with the indices of the resources it depends on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    dependencies: Vec<Vec<usize>>,
+}
+
+/// What became of a task in a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<E> {
+    Succeeded,
+    Failed(E),
+    /// Not started because the task at this index, one of its dependencies, failed or was
+    /// skipped itself.
+    Skipped(usize),
+    /// Not started because the run was cancelled.
+    Cancelled,
+}
+
+impl<E> Outcome<E> {
+    pub fn is_succeeded(&self) -> bool {
+        matches!(self, Self::Succeeded)
+    }
+
+    fn is_failed_or_skipped(&self) -> bool {
+        matches!(self, Self::Failed(_) | Self::Skipped(_))
+    }
+}
+
+struct Schedule<E> {
+    waiting_on: Vec<usize>,
+    ready: VecDeque<usize>,
+    running: usize,
+    outcomes: Vec<Option<Outcome<E>>>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl Graph {
+    pub fn new(mut dependencies: Vec<Vec<usize>>) -> Self {
+        for deps in &mut dependencies {
+            deps.sort_unstable();
+            deps.dedup();
+        }
+
+        Self { dependencies }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dependencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
+    pub fn dependencies(&self, index: usize) -> &[usize] {
+        &self.dependencies[index]
+    }
+
+    /// The indices of the resources that depend on the resource at `index`.
+    pub fn dependents(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.dependencies
+            .iter()
+            .enumerate()
+            .filter(move |(_, deps)| deps.contains(&index))
+            .map(|(dependent, _)| dependent)
+    }
+
+    /// The same graph with every edge turned around, so that dependents come first, e.g. to
+    /// destroy resources.
+    pub fn reversed(&self) -> Self {
+        Self::new(
+            (0..self.len())
+                .map(|index| self.dependents(index).collect())
+                .collect(),
+        )
+    }
+
+    /// Runs `task` for every index on up to `parallelism` threads, starting each one as soon as
+    /// all of its dependencies have succeeded. A failed task does not stop the others, but every
+    /// task depending on it is skipped. Once `cancellation` is cancelled no more tasks are
+    /// started, and the run ends when the running ones have finished.
+    pub fn run<E: Send>(
+        &self,
+        parallelism: usize,
+        cancellation: &CancellationToken,
+        task: impl Fn(usize) -> Result<(), E> + Sync,
+    ) -> Vec<Outcome<E>> {
+        let waiting_on = self.dependencies.iter().map(Vec::len).collect::<Vec<_>>();
+        let ready = (0..self.len()).filter(|&i| waiting_on[i] == 0).collect();
+        let schedule = Mutex::new(Schedule {
+            waiting_on,
+            ready,
+            running: 0,
+            outcomes: (0..self.len()).map(|_| None).collect(),
+            panic: None,
+        });
+        let changed = Condvar::new();
+        let dependents = (0..self.len())
+            .map(|index| self.dependents(index).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        thread::scope(|scope| {
+            for _ in 0..parallelism.clamp(1, self.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some(index) = next_task(&schedule, &changed, cancellation) {
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| task(index)));
+
+                        let mut schedule = lock(&schedule);
+                        schedule.running -= 1;
+                        match result {
+                            Ok(Ok(())) => {
+                                schedule.outcomes[index] = Some(Outcome::Succeeded);
+                                for &dependent in &dependents[index] {
+                                    schedule.waiting_on[dependent] -= 1;
+                                    if schedule.waiting_on[dependent] == 0 {
+                                        schedule.ready.push_back(dependent);
+                                    }
+                                }
+                            }
+                            Ok(Err(err)) => {
+                                schedule.outcomes[index] = Some(Outcome::Failed(err));
+                            }
+                            Err(payload) => {
+                                schedule.panic.get_or_insert(payload);
+                            }
+                        }
+                        changed.notify_all();
+                    }
+                });
+            }
+        });
+
+        let schedule = schedule.into_inner().unwrap_or_else(|err| err.into_inner());
+        if let Some(payload) = schedule.panic {
+            panic::resume_unwind(payload);
+        }
+
+        self.skip_unfinished(schedule.outcomes)
+    }
+
+    /// Like [`Graph::run`], but polls up to `parallelism` task futures at once within the
+    /// calling task instead of using threads, so it works on any executor.
+    pub async fn run_async<E, F>(
+        &self,
+        parallelism: usize,
+        cancellation: &CancellationToken,
+        task: impl Fn(usize) -> F,
+    ) -> Vec<Outcome<E>>
+    where
+        F: Future<Output = Result<(), E>>,
+    {
+        let mut waiting_on = self.dependencies.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = (0..self.len())
+            .filter(|&i| waiting_on[i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut running: Vec<(usize, Pin<Box<F>>)> = Vec::new();
+        let mut outcomes = (0..self.len()).map(|_| None).collect::<Vec<_>>();
+
+        future::poll_fn(|cx| loop {
+            while !cancellation.is_cancelled() && running.len() < parallelism.max(1) {
+                match ready.pop_front() {
+                    Some(index) => running.push((index, Box::pin(task(index)))),
+                    None => break,
+                }
+            }
+
+            if running.is_empty() {
+                return Poll::Ready(());
+            }
+
+            let mut finished = false;
+            let mut position = 0;
+            while position < running.len() {
+                let Poll::Ready(result) = running[position].1.as_mut().poll(cx) else {
+                    position += 1;
+                    continue;
+                };
+
+                let (index, _) = running.remove(position);
+                finished = true;
+                match result {
+                    Ok(()) => {
+                        outcomes[index] = Some(Outcome::Succeeded);
+                        for dependent in self.dependents(index) {
+                            waiting_on[dependent] -= 1;
+                            if waiting_on[dependent] == 0 {
+                                ready.push_back(dependent);
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        outcomes[index] = Some(Outcome::Failed(err));
+                    }
+                }
+            }
+
+            if !finished {
+                return Poll::Pending;
+            }
+        })
+        .await;
+
+        self.skip_unfinished(outcomes)
+    }
+
+    /// Marks every task that never started as skipped if one of its dependencies failed or was
+    /// skipped, and as cancelled otherwise.
+    fn skip_unfinished<E>(&self, mut outcomes: Vec<Option<Outcome<E>>>) -> Vec<Outcome<E>> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..self.len() {
+                if outcomes[index].is_some() {
+                    continue;
+                }
+
+                let blocked_by = self.dependencies[index].iter().copied().find(|&dep| {
+                    outcomes[dep]
+                        .as_ref()
+                        .is_some_and(Outcome::is_failed_or_skipped)
+                });
+                if let Some(dep) = blocked_by {
+                    outcomes[index] = Some(Outcome::Skipped(dep));
+                    changed = true;
+                }
+            }
+        }
+
+        outcomes
+            .into_iter()
+            .map(|outcome| outcome.unwrap_or(Outcome::Cancelled))
+            .collect()
+    }
+}
+
+/// Waits for a task to become ready, or returns `None` once no more tasks will be started.
+fn next_task<E>(
+    schedule: &Mutex<Schedule<E>>,
+    changed: &Condvar,
+    cancellation: &CancellationToken,
+) -> Option<usize> {
+    let mut schedule = lock(schedule);
+
+    loop {
+        if schedule.panic.is_some() || cancellation.is_cancelled() {
+            return None;
+        }
+
+        if let Some(index) = schedule.ready.pop_front() {
+            schedule.running += 1;
+            return Some(index);
+        }
+
+        if schedule.running == 0 {
+            return None;
+        }
+
+        schedule = changed
+            .wait(schedule)
+            .unwrap_or_else(|err| err.into_inner());
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Barrier,
+        },
+        time::Duration,
+    };
+
+    #[test]
+    fn runs_dependencies_first() {
+        // 0 depends on 1 and 2, which both depend on 3.
+        let graph = Graph::new(vec![vec![1, 2], vec![3], vec![3], vec![]]);
+        let order = Mutex::new(Vec::new());
+
+        let outcomes = graph.run(4, &CancellationToken::new(), |index| {
+            lock(&order).push(index);
+            Ok::<_, ()>(())
+        });
+        assert!(outcomes.iter().all(Outcome::is_succeeded));
+
+        let order = lock(&order);
+        let position = |i| order.iter().position(|&o| o == i).unwrap();
+        assert_eq!(order.len(), 4);
+        assert!(position(3) < position(1) && position(3) < position(2));
+        assert!(position(1) < position(0) && position(2) < position(0));
+
+        assert_eq!(graph.reversed().dependencies(3), &[1, 2]);
+    }
+
+    #[test]
+    fn limits_parallelism() {
+        // Each task waits for two others, so the run only finishes if three run at once.
+        let graph = Graph::new(vec![vec![]; 6]);
+        let barrier = Barrier::new(3);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        graph.run(3, &CancellationToken::new(), |_| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            barrier.wait();
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok::<_, ()>(())
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn overlaps_async_tasks() {
+        let graph = Graph::new(vec![vec![], vec![], vec![0, 1]]);
+        let order = Mutex::new(Vec::new());
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let outcomes = block_on(graph.run_async(2, &CancellationToken::new(), |index| {
+            let (order, running, peak) = (&order, &running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                crate::sleep(Duration::from_millis(30)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                lock(order).push(index);
+                Ok::<_, ()>(())
+            }
+        }));
+
+        assert!(outcomes.iter().all(Outcome::is_succeeded));
+        assert_eq!(lock(&order)[2], 2);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    /// Polls `future` on the current thread until it completes.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct ThreadWaker(thread::Thread);
+
+        impl std::task::Wake for ThreadWaker {
+            fn wake(self: std::sync::Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = std::sync::Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = std::task::Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn skips_dependents_of_a_failed_task() {
+        // 2 depends on 1, which depends on 0, and 3 only depends on 0.
+        let graph = Graph::new(vec![vec![], vec![0], vec![1], vec![0]]);
+        let started = AtomicUsize::new(0);
+
+        let outcomes = graph.run(2, &CancellationToken::new(), |index| {
+            started.fetch_add(1, Ordering::SeqCst);
+            if index == 1 {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::Succeeded,
+                Outcome::Failed("failed"),
+                Outcome::Skipped(1),
+                Outcome::Succeeded,
+            ]
+        );
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn starts_no_tasks_once_cancelled() {
+        // 1 depends on 0, and 2 on 1.
+        let graph = Graph::new(vec![vec![], vec![0], vec![1]]);
+        let cancellation = CancellationToken::new();
+
+        let outcomes = graph.run(1, &cancellation, |index| {
+            if index == 0 {
+                cancellation.cancel();
+            }
+            Ok::<_, ()>(())
+        });
+
+        assert_eq!(
+            outcomes,
+            vec![Outcome::Succeeded, Outcome::Cancelled, Outcome::Cancelled]
+        );
+    }
+}
@@ -0,0 +1,513 @@
+use std::{
+    any,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{sleep, Context, Operation, ProviderError, Values};
+
+/// Runs the remaining layers and the provider operation, returning the resource outputs.
+pub type Next<'a> = dyn FnMut() -> Result<Values, ProviderError> + 'a;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Like `Next`, for the operations of an async provider.
+pub type NextAsync<'a> =
+    dyn Fn() -> BoxFuture<'a, Result<Values, ProviderError>> + Send + Sync + 'a;
+
+/// Middleware around every provider operation. A layer may inspect the call, call `next` any
+/// number of times, or answer without calling it at all.
+pub trait Layer: Send + Sync {
+    fn call(
+        &self,
+        ctx: &Context,
+        inputs: &Values,
+        next: &mut Next<'_>,
+    ) -> Result<Values, ProviderError>;
+
+    /// Like `call`, around the operations of an async provider. A layer that does not implement
+    /// it fails those operations.
+    fn call_async<'a>(
+        &'a self,
+        ctx: &'a Context,
+        _inputs: &'a Values,
+        _next: &'a NextAsync<'_>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>> {
+        let layer = any::type_name::<Self>();
+
+        Box::pin(async move {
+            Err(ProviderError::new(format!(
+                "Layer `{}` does not support async providers, so {} of `{}` was not started",
+                layer, ctx.operation, ctx.resource
+            )))
+        })
+    }
+}
+
+/// A provider wrapped in a stack of layers. The first layer added is the outermost one and sees
+/// every operation first.
+pub struct Layered<P> {
+    inner: P,
+    layers: Vec<Box<dyn Layer>>,
+}
+
+impl<P> Layered<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn layer(mut self, layer: impl Layer + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Performs the operation in `ctx` through every layer, with `call` performing it on the
+    /// wrapped provider.
+    pub fn call(
+        &self,
+        ctx: &Context,
+        inputs: &Values,
+        call: &mut dyn FnMut(&P) -> Result<Values, ProviderError>,
+    ) -> Result<Values, ProviderError> {
+        self.call_layer(0, ctx, inputs, call)
+    }
+
+    fn call_layer(
+        &self,
+        depth: usize,
+        ctx: &Context,
+        inputs: &Values,
+        call: &mut dyn FnMut(&P) -> Result<Values, ProviderError>,
+    ) -> Result<Values, ProviderError> {
+        match self.layers.get(depth) {
+            Some(layer) => layer.call(ctx, inputs, &mut || {
+                self.call_layer(depth + 1, ctx, inputs, call)
+            }),
+            None => call(&self.inner),
+        }
+    }
+
+    /// Like `call`, but awaits the operation, which `call` performs on the wrapped async provider
+    /// and `target`, e.g. the resource. Layers may call the operation more than once, so each call
+    /// borrows `target` in turn.
+    pub async fn call_async<T: Send + ?Sized>(
+        &self,
+        ctx: &Context,
+        inputs: &Values,
+        target: &mut T,
+        call: impl for<'r> Fn(
+                &'r P,
+                &'r Context,
+                &'r mut T,
+            ) -> BoxFuture<'r, Result<Values, ProviderError>>
+            + Send
+            + Sync,
+    ) -> Result<Values, ProviderError>
+    where
+        P: Sync,
+    {
+        let target = Mutex::new(Some(target));
+        let (target, call) = (&target, &call);
+
+        let perform = move || {
+            Box::pin(async move {
+                // A call dropped before it finished, e.g. by a layer giving up on it, takes the
+                // target with it.
+                let borrowed = lock(target).take().ok_or_else(|| {
+                    ProviderError::new(format!(
+                        "{} of `{}` was called again after an earlier call was dropped",
+                        ctx.operation, ctx.resource
+                    ))
+                })?;
+                let result = call(&self.inner, ctx, &mut *borrowed).await;
+                *lock(target) = Some(borrowed);
+                result
+            }) as BoxFuture<'_, _>
+        };
+
+        self.call_layer_async(0, ctx, inputs, &perform).await
+    }
+
+    fn call_layer_async<'a>(
+        &'a self,
+        depth: usize,
+        ctx: &'a Context,
+        inputs: &'a Values,
+        perform: &'a NextAsync<'a>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>>
+    where
+        P: Sync,
+    {
+        match self.layers.get(depth) {
+            Some(layer) => Box::pin(async move {
+                let next = move || self.call_layer_async(depth + 1, ctx, inputs, perform);
+                layer.call_async(ctx, inputs, &next).await
+            }),
+            None => perform(),
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Logs every operation with its outcome and duration, to stderr unless given another sink.
+pub struct Logging {
+    sink: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+impl Logging {
+    pub fn new() -> Self {
+        Self::to(|line| eprintln!("{}", line))
+    }
+
+    pub fn to(sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Box::new(sink),
+        }
+    }
+}
+
+impl Default for Logging {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layer for Logging {
+    fn call(
+        &self,
+        ctx: &Context,
+        _inputs: &Values,
+        next: &mut Next<'_>,
+    ) -> Result<Values, ProviderError> {
+        let start = Instant::now();
+        let result = next();
+        self.log(ctx, &result, start);
+        result
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        ctx: &'a Context,
+        _inputs: &'a Values,
+        next: &'a NextAsync<'_>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = next().await;
+            self.log(ctx, &result, start);
+            result
+        })
+    }
+}
+
+impl Logging {
+    fn log(&self, ctx: &Context, result: &Result<Values, ProviderError>, start: Instant) {
+        let outcome = match result {
+            Ok(_) => String::from("ok"),
+            Err(err) => format!("failed: {}", err),
+        };
+
+        (self.sink)(&format!(
+            "{} '{}' ({}) {} in {:?}",
+            ctx.operation,
+            ctx.resource,
+            ctx.resource_type,
+            outcome,
+            start.elapsed()
+        ));
+    }
+}
+
+/// Spaces out operations so that at most `calls` start within any window of `per`.
+pub struct RateLimit {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimit {
+    pub fn new(calls: u32, per: Duration) -> Self {
+        Self {
+            interval: per / calls.max(1),
+            next_slot: Mutex::new(None),
+        }
+    }
+}
+
+impl Layer for RateLimit {
+    fn call(
+        &self,
+        _ctx: &Context,
+        _inputs: &Values,
+        next: &mut Next<'_>,
+    ) -> Result<Values, ProviderError> {
+        thread::sleep(self.reserve());
+        next()
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        _ctx: &'a Context,
+        _inputs: &'a Values,
+        next: &'a NextAsync<'_>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>> {
+        Box::pin(async move {
+            sleep(self.reserve()).await;
+            next().await
+        })
+    }
+}
+
+impl RateLimit {
+    /// Takes the next free slot and returns how long to wait for it.
+    fn reserve(&self) -> Duration {
+        let mut next_slot = lock(&self.next_slot);
+        let now = Instant::now();
+        let slot = next_slot.map_or(now, |slot| slot.max(now));
+        *next_slot = Some(slot + self.interval);
+        slot - now
+    }
+}
+
+/// Answers reads from the outputs of the last successful operation on the same resource with the
+/// same inputs. Cloned caches share their entries.
+#[derive(Clone, Default)]
+pub struct Cache {
+    entries: Arc<Mutex<HashMap<String, (Values, Values)>>>,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&self) {
+        lock(&self.entries).clear();
+    }
+}
+
+impl Layer for Cache {
+    fn call(
+        &self,
+        ctx: &Context,
+        inputs: &Values,
+        next: &mut Next<'_>,
+    ) -> Result<Values, ProviderError> {
+        if let Some(outputs) = self.cached(ctx, inputs) {
+            return Ok(outputs);
+        }
+
+        let result = next();
+        self.store(ctx, inputs, &result);
+        result
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        ctx: &'a Context,
+        inputs: &'a Values,
+        next: &'a NextAsync<'_>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>> {
+        Box::pin(async move {
+            if let Some(outputs) = self.cached(ctx, inputs) {
+                return Ok(outputs);
+            }
+
+            let result = next().await;
+            self.store(ctx, inputs, &result);
+            result
+        })
+    }
+}
+
+impl Cache {
+    /// The outputs to answer a read with, if cached for the same inputs.
+    fn cached(&self, ctx: &Context, inputs: &Values) -> Option<Values> {
+        if ctx.operation != Operation::Read {
+            return None;
+        }
+
+        lock(&self.entries)
+            .get(&ctx.resource)
+            .filter(|(cached_inputs, _)| cached_inputs == inputs)
+            .map(|(_, outputs)| outputs.clone())
+    }
+
+    fn store(&self, ctx: &Context, inputs: &Values, result: &Result<Values, ProviderError>) {
+        let mut entries = lock(&self.entries);
+
+        match (result, ctx.operation) {
+            (Ok(_), Operation::Delete) | (Err(_), _) => {
+                entries.remove(&ctx.resource);
+            }
+            (Ok(outputs), _) => {
+                entries.insert(ctx.resource.clone(), (inputs.clone(), outputs.clone()));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationMetrics {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_time: Duration,
+}
+
+/// Counts calls, errors and time spent per operation and resource type. Cloned metrics share
+/// their counters, so a clone can be kept to read them after adding the layer.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    counters: Arc<Mutex<HashMap<(String, Operation), OperationMetrics>>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, resource_type: &str, operation: Operation) -> OperationMetrics {
+        lock(&self.counters)
+            .get(&(resource_type.to_string(), operation))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn total_calls(&self) -> u64 {
+        lock(&self.counters).values().map(|m| m.calls).sum()
+    }
+}
+
+impl Layer for Metrics {
+    fn call(
+        &self,
+        ctx: &Context,
+        _inputs: &Values,
+        next: &mut Next<'_>,
+    ) -> Result<Values, ProviderError> {
+        let start = Instant::now();
+        let result = next();
+        self.count(ctx, &result, start);
+        result
+    }
+
+    fn call_async<'a>(
+        &'a self,
+        ctx: &'a Context,
+        _inputs: &'a Values,
+        next: &'a NextAsync<'_>,
+    ) -> BoxFuture<'a, Result<Values, ProviderError>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = next().await;
+            self.count(ctx, &result, start);
+            result
+        })
+    }
+}
+
+impl Metrics {
+    fn count(&self, ctx: &Context, result: &Result<Values, ProviderError>, start: Instant) {
+        let mut counters = lock(&self.counters);
+        let metrics = counters
+            .entry((ctx.resource_type.to_string(), ctx.operation))
+            .or_default();
+        metrics.calls += 1;
+        metrics.errors += u64::from(result.is_err());
+        metrics.total_time += start.elapsed();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx(operation: Operation) -> Context {
+        Context::new("a", "Foo", operation)
+    }
+
+    struct Tag(&'static str, Arc<Mutex<Vec<&'static str>>>);
+
+    impl Layer for Tag {
+        fn call(
+            &self,
+            _ctx: &Context,
+            _inputs: &Values,
+            next: &mut Next<'_>,
+        ) -> Result<Values, ProviderError> {
+            lock(&self.1).push(self.0);
+            next()
+        }
+    }
+
+    #[test]
+    fn calls_layers_outermost_first() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let layered = Layered::new(())
+            .layer(Tag("outer", order.clone()))
+            .layer(Tag("inner", order.clone()));
+
+        layered
+            .call(&ctx(Operation::Create), &Values::new(), &mut |_| {
+                lock(&order).push("provider");
+                Ok(Values::new())
+            })
+            .unwrap();
+
+        assert_eq!(*lock(&order), vec!["outer", "inner", "provider"]);
+    }
+
+    #[test]
+    fn caches_reads_until_inputs_change() {
+        let layered = Layered::new(()).layer(Cache::new());
+        let inputs = Values::from([(String::from("size"), json!(1))]);
+        let mut calls = 0;
+        let mut call = |_: &()| {
+            calls += 1;
+            Ok(Values::from([(String::from("id"), json!(calls))]))
+        };
+
+        layered
+            .call(&ctx(Operation::Create), &inputs, &mut call)
+            .unwrap();
+        let outputs = layered
+            .call(&ctx(Operation::Read), &inputs, &mut call)
+            .unwrap();
+        assert_eq!(outputs["id"], json!(1));
+
+        layered
+            .call(&ctx(Operation::Read), &Values::new(), &mut call)
+            .unwrap();
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn rate_limit_spaces_out_calls() {
+        let layered = Layered::new(()).layer(RateLimit::new(2, Duration::from_millis(40)));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            layered
+                .call(&ctx(Operation::Read), &Values::new(), &mut |_| {
+                    Ok(Values::new())
+                })
+                .unwrap();
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,44 @@
+mod attributes;
+mod blocking;
+mod cancel;
+mod config;
+mod diagnostic;
+mod diagram;
+mod fault;
+mod graph;
+mod layer;
+mod mock;
+mod plan;
+mod provider;
+mod record;
+mod report;
+pub mod rest;
+mod retry;
+mod state;
+mod timeout;
+mod timer;
+
+pub use attributes::{to_value, AttributeError, Attributes, Value, Values};
+pub use blocking::{offload, Offload};
+pub use cancel::CancellationToken;
+pub use config::{ConfigError, ConfigSource};
+pub use diagnostic::{Diagnostic, Severity};
+pub use diagram::{ResourceGraph, ResourceNode};
+pub use fault::{Fault, FaultInjector, FaultKind, InjectedFault};
+pub use graph::{Graph, Outcome, DEFAULT_PARALLELISM};
+pub use layer::{
+    BoxFuture, Cache, Layer, Layered, Logging, Metrics, Next, NextAsync, OperationMetrics,
+    RateLimit,
+};
+pub use mock::{MockCall, MockProvider, MockResource};
+pub use plan::{Action, Fingerprints, Plan, PlanError, PlannedChange};
+pub use provider::{Context, Operation, ProviderError};
+pub use record::{Interaction, Recorder, RecorderConfig};
+pub use report::{ApplyError, ApplyReport, FailedResource, ResourceOutcome, SkippedResource};
+pub use retry::{Attempts, RetryPolicy};
+pub use state::{ProviderInfo, ResourceRecord, ResourceStatus, StateError, StateFile};
+pub use timeout::Timeouts;
+pub use timer::{sleep, Sleep};
+
+pub use serde;
+pub use serde_json;
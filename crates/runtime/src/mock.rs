@@ -0,0 +1,206 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use crate::{Context, Operation, ProviderError, Values};
+
+/// A provider operation recorded by [`MockProvider`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockCall {
+    pub operation: Operation,
+    pub resource: String,
+    pub resource_type: String,
+    pub inputs: Values,
+    pub outputs: Values,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResource {
+    pub resource_type: String,
+    pub inputs: Values,
+    pub outputs: Values,
+}
+
+#[derive(Debug, Default)]
+struct MockStore {
+    resources: BTreeMap<String, MockResource>,
+    calls: Vec<MockCall>,
+}
+
+/// An in-memory provider for tests, available to every `#[provider(mock)]` module. Resources
+/// are kept in a map keyed by name and every operation is recorded in order. Reading, updating
+/// or deleting a resource the mock does not hold fails with a not found error, like it would
+/// against a real service.
+///
+/// Configured outputs require provider_macro's `serde` feature to be applied to resources.
+#[derive(Debug, Default)]
+pub struct MockProvider {
+    outputs: HashMap<String, Values>,
+    store: Mutex<MockStore>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `outputs` for every created or updated resource of type `resource_type`.
+    pub fn with_outputs(mut self, resource_type: &str, outputs: Values) -> Self {
+        self.outputs.insert(resource_type.to_string(), outputs);
+        self
+    }
+
+    /// Starts with `name` already present, as if it had been created earlier.
+    pub fn with_resource(self, name: &str, resource: MockResource) -> Self {
+        self.store().resources.insert(name.to_string(), resource);
+        self
+    }
+
+    /// Records the operation in `ctx` and applies it to the stored resources. Returns the outputs
+    /// the resource should have afterwards.
+    pub fn handle(
+        &self,
+        ctx: &Context,
+        inputs: Values,
+        outputs: Values,
+    ) -> Result<Values, ProviderError> {
+        let mut store = self.store();
+        store.calls.push(MockCall {
+            operation: ctx.operation,
+            resource: ctx.resource.clone(),
+            resource_type: ctx.resource_type.to_string(),
+            inputs: inputs.clone(),
+            outputs: outputs.clone(),
+        });
+
+        let exists = store.resources.contains_key(&ctx.resource);
+        if ctx.operation != Operation::Create && !exists {
+            return Err(ProviderError::not_found(format!(
+                "Resource `{}` does not exist",
+                ctx.resource
+            )));
+        }
+
+        match ctx.operation {
+            Operation::Create | Operation::Update => {
+                let mut outputs = outputs;
+                if let Some(configured) = self.outputs.get(ctx.resource_type) {
+                    outputs.extend(configured.clone());
+                }
+
+                store.resources.insert(
+                    ctx.resource.clone(),
+                    MockResource {
+                        resource_type: ctx.resource_type.to_string(),
+                        inputs,
+                        outputs: outputs.clone(),
+                    },
+                );
+
+                Ok(outputs)
+            }
+            Operation::Read => Ok(store.resources[&ctx.resource].outputs.clone()),
+            Operation::Delete => {
+                store.resources.remove(&ctx.resource);
+                Ok(Values::new())
+            }
+        }
+    }
+
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.store().calls.clone()
+    }
+
+    /// Every recorded operation with the name of its resource, in call order.
+    pub fn operations(&self) -> Vec<(Operation, String)> {
+        self.store()
+            .calls
+            .iter()
+            .map(|call| (call.operation, call.resource.clone()))
+            .collect()
+    }
+
+    pub fn resource(&self, name: &str) -> Option<MockResource> {
+        self.store().resources.get(name).cloned()
+    }
+
+    pub fn resources(&self) -> Vec<String> {
+        self.store().resources.keys().cloned().collect()
+    }
+
+    /// Forgets recorded calls, keeping the stored resources.
+    pub fn clear_calls(&self) {
+        self.store().calls.clear();
+    }
+
+    /// Panics unless exactly `expected` operations were performed, in that order.
+    #[track_caller]
+    pub fn assert_operations(&self, expected: &[(Operation, &str)]) {
+        let expected = expected
+            .iter()
+            .map(|(operation, name)| (*operation, name.to_string()))
+            .collect::<Vec<(Operation, String)>>();
+
+        assert_eq!(
+            self.operations(),
+            expected,
+            "unexpected provider operations"
+        );
+    }
+
+    /// Panics unless `operation` was performed on `name` at least once.
+    #[track_caller]
+    pub fn assert_called(&self, operation: Operation, name: &str) {
+        let called = self
+            .store()
+            .calls
+            .iter()
+            .any(|call| call.operation == operation && call.resource == name);
+
+        assert!(called, "expected {} of `{}` to be called", operation, name);
+    }
+
+    fn store(&self) -> std::sync::MutexGuard<'_, MockStore> {
+        self.store.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn ctx(name: &str, operation: Operation) -> Context {
+        Context::new(name, "Foo", operation)
+    }
+
+    #[test]
+    fn stores_resources_and_records_calls() {
+        let mock = MockProvider::new()
+            .with_outputs("Foo", Values::from([(String::from("id"), json!("abc"))]));
+
+        let outputs = mock
+            .handle(&ctx("a", Operation::Create), Values::new(), Values::new())
+            .unwrap();
+        assert_eq!(outputs["id"], json!("abc"));
+        assert_eq!(mock.resources(), vec![String::from("a")]);
+
+        mock.handle(&ctx("a", Operation::Delete), Values::new(), Values::new())
+            .unwrap();
+        assert!(mock.resources().is_empty());
+
+        mock.assert_operations(&[(Operation::Create, "a"), (Operation::Delete, "a")]);
+    }
+
+    #[test]
+    fn returns_error_for_missing_resource() {
+        let mock = MockProvider::new();
+
+        assert!(mock
+            .handle(&ctx("a", Operation::Read), Values::new(), Values::new())
+            .unwrap_err()
+            .is_not_found());
+        mock.assert_called(Operation::Read, "a");
+    }
+}
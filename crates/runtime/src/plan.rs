@@ -0,0 +1,741 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AttributeError, Attributes, ProviderInfo, ResourceRecord, ResourceStatus, StateError,
+    StateFile, Value, Values,
+};
+
+const PLAN_FORMAT_VERSION: u32 = 1;
+
+/// Shown, and exported, in place of the value of a sensitive field.
+const SENSITIVE_VALUE: &str = "(sensitive value)";
+
+#[derive(Debug)]
+pub enum PlanError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    UnsupportedFormat(u32),
+    /// The plan was not made by a State, so there is nothing to check it against.
+    MissingFingerprints,
+    StateChanged,
+    DefinitionChanged,
+    ProviderChanged {
+        planned: ProviderInfo,
+        current: ProviderInfo,
+    },
+    /// The fingerprints match, but planning again gives different changes, e.g. because an input
+    /// is read from the environment. This includes sensitive values, which are masked alike.
+    ChangesDiffer,
+    /// While the plan was applied, a resource came to need another action than the planned one,
+    /// e.g. because a dependency it reads was replaced.
+    ActionChanged {
+        resource: String,
+        planned: Action,
+        current: Action,
+    },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Could not access plan file: {}", err),
+            Self::Parse(err) => write!(f, "Invalid plan: {}", err),
+            Self::UnsupportedFormat(version) => {
+                write!(f, "Unsupported plan format version {}", version)
+            }
+            Self::MissingFingerprints => {
+                write!(f, "The plan has no fingerprints, so it cannot be applied")
+            }
+            Self::StateChanged => write!(f, "The state changed since the plan was made"),
+            Self::DefinitionChanged => {
+                write!(f, "The state definition changed since the plan was made")
+            }
+            Self::ProviderChanged { planned, current } => write!(
+                f,
+                "The provider changed from {} {} to {} {} since the plan was made",
+                planned.name, planned.version, current.name, current.version
+            ),
+            Self::ChangesDiffer => write!(f, "The planned changes no longer match the definition"),
+            Self::ActionChanged {
+                resource,
+                planned,
+                current,
+            } => write!(
+                f,
+                "The planned action for `{}` was {}, but it is now {}",
+                resource, planned, current
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl From<io::Error> for PlanError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for PlanError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Create,
+    Update,
+    /// Deletes the recorded resource and creates it again, because an input that cannot be
+    /// updated in place changed.
+    Replace,
+    Delete,
+    NoOp,
+}
+
+impl Action {
+    /// Resources missing from state are created, and recorded resources whose inputs changed are
+    /// updated, or replaced if one of the `force_new` inputs changed. Uncertain resources are
+    /// updated too, to bring them back in line with their inputs; the State reads them first and
+    /// creates them instead if the provider does not have them.
+    pub fn for_resource(
+        record: Option<&ResourceRecord>,
+        inputs: &Values,
+        force_new: &[&str],
+    ) -> Self {
+        match record {
+            None => Self::Create,
+            Some(record)
+                if force_new
+                    .iter()
+                    .any(|&field| record.inputs.get(field) != inputs.get(field)) =>
+            {
+                Self::Replace
+            }
+            Some(record) if record.inputs != *inputs => Self::Update,
+            Some(record) if record.status == ResourceStatus::Uncertain => Self::Update,
+            Some(_) => Self::NoOp,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Replace => "replace",
+            Self::Delete => "delete",
+            Self::NoOp => "no-op",
+        };
+
+        write!(f, "{}", action)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    /// The resource type and name, e.g. `Subnet.a`.
+    pub address: String,
+    pub name: String,
+    pub resource_type: String,
+    pub action: Action,
+    /// Why the action is needed, e.g. which inputs changed.
+    pub reason: String,
+    /// The recorded inputs and outputs, empty for a resource to create.
+    pub before: Values,
+    /// The inputs to apply, with the outputs if they are known, empty for a resource to delete.
+    pub after: Values,
+    /// Fields whose values are only known after apply, such as the outputs of a resource to
+    /// create.
+    pub unknown: Vec<String>,
+    /// Fields whose values are masked when shown.
+    pub sensitive: Vec<String>,
+    /// Changed fields that force the resource to be replaced.
+    pub forces_replacement: Vec<String>,
+    /// Names of the resources this one depends on.
+    pub depends_on: Vec<String>,
+}
+
+impl PlannedChange {
+    pub fn new(name: &str, resource_type: &str, action: Action) -> Self {
+        Self {
+            address: format!("{}.{}", resource_type, name),
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            action,
+            reason: String::new(),
+            before: Values::new(),
+            after: Values::new(),
+            unknown: Vec::new(),
+            sensitive: Vec::new(),
+            forces_replacement: Vec::new(),
+            depends_on: Vec::new(),
+        }
+    }
+
+    pub fn with_dependencies(mut self, depends_on: &[&str]) -> Self {
+        self.depends_on = depends_on.iter().map(|d| d.to_string()).collect();
+        self
+    }
+
+    /// The change `action` makes to `resource`, which is recorded as `record`.
+    pub fn for_resource<R: Attributes>(
+        name: &str,
+        resource: &R,
+        record: Option<&ResourceRecord>,
+        action: Action,
+    ) -> Result<Self, AttributeError> {
+        let mut change = Self::new(name, R::TYPE_NAME, action);
+        if let Some(record) = record {
+            change.before = record.inputs.clone();
+            change.before.extend(record.outputs.clone());
+        }
+
+        if action != Action::Delete {
+            change.after = resource.inputs()?;
+            match action {
+                Action::NoOp => change.after.extend(resource.outputs()?),
+                _ => change.unknown = resource.outputs()?.into_keys().collect(),
+            }
+        }
+
+        change.sensitive = R::SENSITIVE_FIELDS.iter().map(|f| f.to_string()).collect();
+        if action == Action::Replace {
+            change.forces_replacement = R::FORCE_NEW_FIELDS
+                .iter()
+                .filter(|&&field| change.before.get(field) != change.after.get(field))
+                .map(|f| f.to_string())
+                .collect();
+        }
+
+        let inputs = resource.inputs()?;
+        let changed = record
+            .map(|record| {
+                record
+                    .inputs
+                    .keys()
+                    .chain(inputs.keys())
+                    .filter(|&field| record.inputs.get(field) != inputs.get(field))
+                    .map(String::as_str)
+                    .collect::<std::collections::BTreeSet<&str>>()
+            })
+            .unwrap_or_default();
+        change.reason = match action {
+            Action::Create => String::from("not in state"),
+            Action::Replace => format!(
+                "{} cannot be updated in place",
+                field_list(change.forces_replacement.iter().map(String::as_str))
+            ),
+            Action::Update if !changed.is_empty() => {
+                format!("{} changed", field_list(changed.into_iter()))
+            }
+            Action::Update => String::from(
+                "uncertain after an operation timed out, so it is read first and created if missing",
+            ),
+            Action::Delete => String::from("not in configuration"),
+            Action::NoOp => String::from("up to date"),
+        };
+
+        Ok(change)
+    }
+
+    fn is_sensitive(&self, field: &str) -> bool {
+        self.sensitive.iter().any(|f| f == field)
+    }
+
+    fn is_unknown(&self, field: &str) -> bool {
+        self.unknown.iter().any(|f| f == field)
+    }
+
+    /// Shows a field value, masked if it is sensitive.
+    fn show(&self, field: &str, value: &Value) -> String {
+        if self.is_sensitive(field) {
+            return String::from(SENSITIVE_VALUE);
+        }
+
+        serde_json::to_string(value).unwrap_or_else(|_| value.to_string())
+    }
+
+    /// The change with the values of its sensitive fields masked.
+    fn masked(&self) -> Self {
+        let mut change = self.clone();
+        for values in [&mut change.before, &mut change.after] {
+            for (field, value) in values.iter_mut() {
+                if self.is_sensitive(field) {
+                    *value = Value::String(String::from(SENSITIVE_VALUE));
+                }
+            }
+        }
+        change
+    }
+
+    /// The new value of `field`, which may only be known after apply.
+    fn show_after(&self, field: &str) -> String {
+        match self.after.get(field) {
+            Some(value) => self.show(field, value),
+            None if self.is_unknown(field) => String::from("(known after apply)"),
+            None => String::from("null"),
+        }
+    }
+
+    /// Writes the change Terraform-style, listing every field of a resource to create or delete,
+    /// and only the changed fields otherwise.
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (marker, description) = match self.action {
+            Action::Create => ("+", "will be created"),
+            Action::Update => ("~", "will be updated in-place"),
+            Action::Replace => ("-/+", "must be replaced"),
+            Action::Delete => ("-", "will be destroyed"),
+            Action::NoOp => return Ok(()),
+        };
+        writeln!(
+            f,
+            "  # {}.{} {}",
+            self.resource_type, self.name, description
+        )?;
+        writeln!(f, "{:>3} {}.{} {{", marker, self.resource_type, self.name)?;
+
+        let mut fields = self
+            .before
+            .keys()
+            .chain(self.after.keys())
+            .chain(&self.unknown)
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+        fields.sort_unstable();
+        fields.dedup();
+
+        let mut lines = Vec::new();
+        for field in fields {
+            let after = self.after.get(field);
+            let removed = after.is_none() && !self.is_unknown(field);
+            let line = match (self.action, self.before.get(field)) {
+                (Action::Create, _) | (_, None) => ("+", self.show_after(field)),
+                (_, Some(before)) if self.action == Action::Delete || removed => {
+                    ("-", format!("{} -> null", self.show(field, before)))
+                }
+                (_, Some(before)) if after == Some(before) => continue,
+                (_, Some(_)) if self.is_sensitive(field) => ("~", self.show_after(field)),
+                (_, Some(before)) => (
+                    "~",
+                    format!("{} -> {}", self.show(field, before), self.show_after(field)),
+                ),
+            };
+            lines.push((line.0, field, line.1));
+        }
+
+        let width = lines
+            .iter()
+            .map(|(_, field, _)| field.len())
+            .max()
+            .unwrap_or(0);
+        for (marker, field, value) in lines {
+            let forces = if self.forces_replacement.iter().any(|f| f == field) {
+                " # forces replacement"
+            } else {
+                ""
+            };
+            writeln!(
+                f,
+                "      {} {:<width$} = {}{}",
+                marker, field, value, forces
+            )?;
+        }
+
+        writeln!(f, "    }}")
+    }
+}
+
+/// Lists field names for a message, e.g. "`size` and `zone`".
+fn field_list<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let fields = fields.map(|f| format!("`{}`", f)).collect::<Vec<_>>();
+
+    match fields.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => fields.concat(),
+    }
+}
+
+/// What a plan was made from, so that applying it later can refuse to run if any of it changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprints {
+    pub state: String,
+    pub definition: String,
+    pub provider: ProviderInfo,
+    /// The planned changes with their sensitive values, which the plan only holds masked.
+    pub changes: String,
+}
+
+impl Fingerprints {
+    /// Fingerprints `state`, the source of the `state!` definition and the `changes` planned
+    /// from them.
+    pub fn new(
+        state: &StateFile,
+        definition: &str,
+        provider: ProviderInfo,
+        changes: &[PlannedChange],
+    ) -> Result<Self, StateError> {
+        let changes = serde_json::to_vec(changes).expect("planned values serialize to JSON");
+
+        Ok(Self {
+            state: fingerprint(state.to_json()?.as_bytes()),
+            definition: fingerprint(definition.as_bytes()),
+            provider,
+            changes: fingerprint(&changes),
+        })
+    }
+}
+
+/// A 64-bit FNV-1a hash, which unlike the standard hasher is stable across Rust versions.
+fn fingerprint(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+/// The changes an apply would make, which can be exported as versioned JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    format_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprints: Option<Fingerprints>,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Self {
+            format_version: PLAN_FORMAT_VERSION,
+            fingerprints: None,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl Plan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fingerprints(mut self, fingerprints: Fingerprints) -> Self {
+        self.fingerprints = Some(fingerprints);
+        self
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PlanError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PlanError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Checks that this plan, saved earlier, is still what `current` was just planned to do.
+    pub fn verify(&self, current: &Plan) -> Result<(), PlanError> {
+        let (Some(planned), Some(now)) = (&self.fingerprints, &current.fingerprints) else {
+            return Err(PlanError::MissingFingerprints);
+        };
+
+        if planned.provider != now.provider {
+            return Err(PlanError::ProviderChanged {
+                planned: planned.provider.clone(),
+                current: now.provider.clone(),
+            });
+        }
+        if planned.definition != now.definition {
+            return Err(PlanError::DefinitionChanged);
+        }
+        if planned.state != now.state {
+            return Err(PlanError::StateChanged);
+        }
+        if self.masked().changes != current.masked().changes || planned.changes != now.changes {
+            return Err(PlanError::ChangesDiffer);
+        }
+
+        Ok(())
+    }
+
+    /// Reads a plan exported by `to_json`, in which the values of sensitive fields are masked.
+    pub fn from_json(json: &str) -> Result<Self, PlanError> {
+        let plan: Self = serde_json::from_str(json)?;
+
+        if plan.format_version != PLAN_FORMAT_VERSION {
+            return Err(PlanError::UnsupportedFormat(plan.format_version));
+        }
+
+        Ok(plan)
+    }
+
+    /// Exports the plan as JSON, with the values of sensitive fields masked and the fields listed
+    /// per change.
+    pub fn to_json(&self) -> Result<String, PlanError> {
+        Ok(serde_json::to_string_pretty(&self.masked())?)
+    }
+
+    /// The plan as exported by `to_json`, with the values of sensitive fields masked.
+    pub fn masked(&self) -> Self {
+        Self {
+            changes: self.changes.iter().map(PlannedChange::masked).collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn push(&mut self, change: PlannedChange) {
+        self.changes.push(change);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PlannedChange> {
+        self.changes.iter().find(|c| c.name == name)
+    }
+
+    /// Checks that `action` is what this plan does to the resource named `name`.
+    pub fn check_action(&self, name: &str, action: Action) -> Result<(), PlanError> {
+        match self.get(name) {
+            Some(change) if change.action == action => Ok(()),
+            Some(change) => Err(PlanError::ActionChanged {
+                resource: name.to_string(),
+                planned: change.action,
+                current: action,
+            }),
+            None => Err(PlanError::ChangesDiffer),
+        }
+    }
+
+    pub fn count(&self, action: Action) -> usize {
+        self.changes.iter().filter(|c| c.action == action).count()
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.changes.iter().any(|c| c.action != Action::NoOp)
+    }
+
+    /// The summary line, counting a replaced resource both as added and destroyed.
+    pub fn summary(&self) -> String {
+        let replaced = self.count(Action::Replace);
+
+        format!(
+            "Plan: {} to add, {} to change, {} to destroy.",
+            self.count(Action::Create) + replaced,
+            self.count(Action::Update),
+            self.count(Action::Delete) + replaced
+        )
+    }
+}
+
+impl fmt::Display for Plan {
+    /// Renders the plan for review, with a block per changed resource and the summary line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.has_changes() {
+            return writeln!(f, "No changes.");
+        }
+
+        for change in self.changes.iter().filter(|c| c.action != Action::NoOp) {
+            change.render(f)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "{}", self.summary())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decides_action_from_recorded_inputs() {
+        let inputs = Values::from([(String::from("size"), serde_json::json!(3))]);
+        let record = ResourceRecord::new("a", "Foo", ProviderInfo::new("acme", "1.0.0"))
+            .with_values(inputs.clone(), Values::new());
+
+        assert_eq!(Action::for_resource(None, &inputs, &[]), Action::Create);
+        assert_eq!(
+            Action::for_resource(Some(&record), &inputs, &["size"]),
+            Action::NoOp
+        );
+        assert_eq!(
+            Action::for_resource(Some(&record), &Values::new(), &[]),
+            Action::Update
+        );
+        assert_eq!(
+            Action::for_resource(Some(&record), &Values::new(), &["size"]),
+            Action::Replace
+        );
+
+        let record = record.with_status(ResourceStatus::Uncertain);
+        assert_eq!(
+            Action::for_resource(Some(&record), &inputs, &[]),
+            Action::Update
+        );
+    }
+
+    fn values(values: serde_json::Value) -> Values {
+        serde_json::from_value(values).unwrap()
+    }
+
+    #[test]
+    fn renders_changes_terraform_style() {
+        let mut plan = Plan::new();
+
+        let mut change = PlannedChange::new("web", "Server", Action::Create);
+        change.after = values(serde_json::json!({"name": "web", "password": "hunter2"}));
+        change.unknown = vec![String::from("id")];
+        change.sensitive = vec![String::from("password")];
+        plan.push(change);
+
+        let mut change = PlannedChange::new("db", "Server", Action::Update);
+        change.before = values(serde_json::json!({"name": "db", "size": 1, "id": "i-1"}));
+        change.after = values(serde_json::json!({"name": "db", "size": 2, "id": "i-1"}));
+        plan.push(change);
+
+        let mut change = PlannedChange::new("disk", "Disk", Action::Replace);
+        change.before = values(serde_json::json!({"zone": "a", "id": "d-1"}));
+        change.after = values(serde_json::json!({"zone": "b"}));
+        change.unknown = vec![String::from("id")];
+        change.forces_replacement = vec![String::from("zone")];
+        plan.push(change);
+
+        let mut change = PlannedChange::new("old", "Disk", Action::Delete);
+        change.before = values(serde_json::json!({"zone": "a"}));
+        plan.push(change);
+
+        plan.push(PlannedChange::new("same", "Disk", Action::NoOp));
+
+        assert_eq!(
+            plan.to_string(),
+            r#"  # Server.web will be created
+  + Server.web {
+      + id       = (known after apply)
+      + name     = "web"
+      + password = (sensitive value)
+    }
+
+  # Server.db will be updated in-place
+  ~ Server.db {
+      ~ size = 1 -> 2
+    }
+
+  # Disk.disk must be replaced
+-/+ Disk.disk {
+      ~ id   = "d-1" -> (known after apply)
+      ~ zone = "a" -> "b" # forces replacement
+    }
+
+  # Disk.old will be destroyed
+  - Disk.old {
+      - zone = "a" -> null
+    }
+
+Plan: 2 to add, 1 to change, 2 to destroy.
+"#
+        );
+        assert_eq!(Plan::new().to_string(), "No changes.\n");
+    }
+
+    #[test]
+    fn reads_back_exported_json() {
+        let inputs = values(serde_json::json!({"size": 3, "zone": "a"}));
+        let record = ResourceRecord::new("a", "Foo", ProviderInfo::new("acme", "1.0.0"))
+            .with_values(
+                values(serde_json::json!({"size": 1, "zone": "a"})),
+                Values::new(),
+            );
+
+        let mut change = PlannedChange::new("a", "Foo", Action::Update).with_dependencies(&["b"]);
+        change.before = record.inputs.clone();
+        change.after = inputs;
+        change.reason = String::from("`size` changed");
+        let mut plan = Plan::new();
+        plan.push(change);
+
+        let json = plan.to_json().unwrap();
+        assert!(json.contains("\"format_version\": 1"));
+        assert!(json.contains("\"address\": \"Foo.a\""));
+        assert!(json.contains("\"action\": \"update\""));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan);
+
+        plan.changes[0].sensitive = vec![String::from("zone")];
+        let json = plan.to_json().unwrap();
+        assert!(json.contains("\"zone\": \"(sensitive value)\""));
+        assert!(!json.contains("\"zone\": \"a\""));
+        assert_eq!(Plan::from_json(&json).unwrap(), plan.masked());
+
+        let json = json.replace("\"format_version\": 1", "\"format_version\": 2");
+        assert_eq!(
+            Plan::from_json(&json).unwrap_err().to_string(),
+            "Unsupported plan format version 2"
+        );
+        assert!(matches!(Plan::from_json("{"), Err(PlanError::Parse(_))));
+    }
+
+    #[test]
+    fn refuses_plans_made_from_something_else() {
+        let provider = ProviderInfo::new("acme", "1.0.0");
+        let plan_from = |state: &StateFile, definition: &str, provider: &ProviderInfo| -> Plan {
+            let fingerprints = Fingerprints::new(state, definition, provider.clone(), &[]).unwrap();
+            Plan::new().with_fingerprints(fingerprints)
+        };
+        let mut state = StateFile::new();
+        let saved = plan_from(&state, "a {}", &provider);
+
+        assert!(saved.verify(&plan_from(&state, "a {}", &provider)).is_ok());
+        assert!(matches!(
+            Plan::new().verify(&saved),
+            Err(PlanError::MissingFingerprints)
+        ));
+        assert!(matches!(
+            saved.verify(&plan_from(&state, "b {}", &provider)),
+            Err(PlanError::DefinitionChanged)
+        ));
+        assert_eq!(
+            saved
+                .verify(&plan_from(
+                    &state,
+                    "a {}",
+                    &ProviderInfo::new("acme", "1.1.0")
+                ))
+                .unwrap_err()
+                .to_string(),
+            "The provider changed from acme 1.0.0 to acme 1.1.0 since the plan was made"
+        );
+
+        let mut current = plan_from(&state, "a {}", &provider);
+        current.push(PlannedChange::new("a", "Foo", Action::Create));
+        assert!(matches!(
+            saved.verify(&current),
+            Err(PlanError::ChangesDiffer)
+        ));
+
+        let with_password = |password: &str| -> Plan {
+            let mut change = PlannedChange::new("a", "Foo", Action::Create);
+            change.after = values(serde_json::json!({ "password": password }));
+            change.sensitive = vec![String::from("password")];
+            let changes = std::slice::from_ref(&change);
+            let fingerprints =
+                Fingerprints::new(&state, "a {}", provider.clone(), changes).unwrap();
+            let mut plan = Plan::new().with_fingerprints(fingerprints);
+            plan.push(change);
+            plan
+        };
+        let saved = Plan::from_json(&with_password("hunter2").to_json().unwrap()).unwrap();
+        assert!(saved.verify(&with_password("hunter2")).is_ok());
+        assert!(matches!(
+            saved.verify(&with_password("hunter3")),
+            Err(PlanError::ChangesDiffer)
+        ));
+
+        state.record(ResourceRecord::new("a", "Foo", provider.clone()));
+        assert!(matches!(
+            saved.verify(&plan_from(&state, "a {}", &provider)),
+            Err(PlanError::StateChanged)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeError, CancellationToken};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Create,
+    Read,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self {
+            Self::Create => "create",
+            Self::Read => "read",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        };
+
+        write!(f, "{}", operation)
+    }
+}
+
+/// Passed to every provider operation, identifying the resource it is performed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Context {
+    pub resource: String,
+    pub resource_type: &'static str,
+    pub operation: Operation,
+    /// When the operation times out. Providers should give up by then, as the State cannot
+    /// interrupt a call in progress.
+    pub deadline: Option<Instant>,
+    /// Cancels the run this operation is part of, which brings the deadline forward to the end
+    /// of its grace period.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Context {
+    pub fn new(resource: &str, resource_type: &'static str, operation: Operation) -> Self {
+        Self {
+            resource: resource.to_string(),
+            resource_type,
+            operation,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Time left until the deadline or the end of the cancellation grace period, whichever comes
+    /// first, if there is one.
+    pub fn remaining(&self) -> Option<Duration> {
+        let grace_deadline = self
+            .cancellation
+            .as_ref()
+            .and_then(CancellationToken::deadline);
+        let deadline = match (self.deadline, grace_deadline) {
+            (Some(deadline), Some(grace_deadline)) => Some(deadline.min(grace_deadline)),
+            (deadline, grace_deadline) => deadline.or(grace_deadline),
+        };
+
+        deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining()
+            .is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Fails once the run is cancelled or with a timeout once the deadline has passed, e.g.
+    /// before retrying an operation.
+    pub fn check_deadline(&self) -> Result<(), ProviderError> {
+        if self.is_cancelled() {
+            return Err(ProviderError::interrupted(format!(
+                "{} of `{}` was not started, the run was interrupted",
+                self.operation, self.resource
+            )));
+        }
+
+        if self.is_expired() {
+            return Err(ProviderError::timeout(format!(
+                "{} of `{}` passed its deadline",
+                self.operation, self.resource
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The error for a call that was still running at its deadline.
+    pub fn timed_out(&self) -> ProviderError {
+        ProviderError::timeout(format!(
+            "{} of `{}` did not finish by its deadline",
+            self.operation, self.resource
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    Fatal,
+    Retryable,
+    Timeout,
+    Interrupted,
+    NotFound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderError {
+    message: String,
+    kind: ErrorKind,
+}
+
+impl ProviderError {
+    /// A fatal error, which is never retried.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::Fatal,
+        }
+    }
+
+    /// A transient error, such as throttling, which may succeed when retried.
+    pub fn retryable(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::Retryable,
+        }
+    }
+
+    /// The operation did not complete in time and may or may not have taken effect, so it is not
+    /// retried.
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::Timeout,
+        }
+    }
+
+    /// The operation was not started because the run was cancelled.
+    pub fn interrupted(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::Interrupted,
+        }
+    }
+
+    /// The resource does not exist, e.g. because the create that was recorded as uncertain never
+    /// took effect.
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ErrorKind::NotFound,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.kind == ErrorKind::Retryable
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.kind == ErrorKind::Timeout
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.kind == ErrorKind::Interrupted
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.kind == ErrorKind::NotFound
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<AttributeError> for ProviderError {
+    fn from(err: AttributeError) -> Self {
+        Self::new(err.to_string())
+    }
+}
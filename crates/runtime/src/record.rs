@@ -0,0 +1,385 @@
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ConfigError, Context, Operation, ProviderError, Values};
+
+const FIXTURE_FORMAT_VERSION: u32 = 1;
+
+/// One provider operation and its result, as stored in a fixture file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub operation: Operation,
+    pub resource: String,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    #[serde(default, skip_serializing_if = "Values::is_empty")]
+    pub inputs: Values,
+    #[serde(default, skip_serializing_if = "Values::is_empty")]
+    pub outputs: Values,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timeout: bool,
+}
+
+impl Interaction {
+    fn new(ctx: &Context, inputs: Values) -> Self {
+        Self {
+            operation: ctx.operation,
+            resource: ctx.resource.clone(),
+            resource_type: ctx.resource_type.to_string(),
+            inputs,
+            outputs: Values::new(),
+            error: None,
+            retryable: false,
+            timeout: false,
+        }
+    }
+
+    /// Whether `other` is the same operation on the same resource with the same inputs.
+    fn matches(&self, other: &Interaction) -> bool {
+        self.operation == other.operation
+            && self.resource == other.resource
+            && self.resource_type == other.resource_type
+            && self.inputs == other.inputs
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} of `{}` ({}) with inputs {}",
+            self.operation,
+            self.resource,
+            self.resource_type,
+            serde_json::to_string(&self.inputs).unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    format_version: u32,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RecorderConfig<C> {
+    Record { path: PathBuf, inner: C },
+    Replay { path: PathBuf },
+}
+
+#[derive(Debug)]
+struct Session {
+    interactions: Vec<Interaction>,
+    position: usize,
+    /// Whether interactions were recorded since the fixture was last written.
+    unsaved: bool,
+}
+
+/// Wraps a provider to record every operation into a fixture file, or replays a fixture without
+/// any provider. Replaying fails as soon as an operation differs from the recorded one.
+///
+/// The fixture is written once recording finishes, by `finish` or else when the recorder is
+/// dropped.
+#[derive(Debug)]
+pub struct Recorder<P> {
+    inner: Option<P>,
+    path: PathBuf,
+    session: Mutex<Session>,
+}
+
+impl<P> Recorder<P> {
+    /// Forwards every operation to `inner` and records them for the fixture at `path`.
+    pub fn record(inner: P, path: impl AsRef<Path>) -> Self {
+        Self {
+            inner: Some(inner),
+            path: path.as_ref().to_path_buf(),
+            session: Mutex::new(Session {
+                interactions: Vec::new(),
+                position: 0,
+                unsaved: false,
+            }),
+        }
+    }
+
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let io_error = |reason: String| ConfigError::Io {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let contents = fs::read_to_string(&path).map_err(|err| io_error(err.to_string()))?;
+        let fixture: Fixture =
+            serde_json::from_str(&contents).map_err(|err| io_error(err.to_string()))?;
+
+        if fixture.format_version != FIXTURE_FORMAT_VERSION {
+            return Err(io_error(format!(
+                "unsupported fixture format version {}",
+                fixture.format_version
+            )));
+        }
+
+        Ok(Self {
+            inner: None,
+            path,
+            session: Mutex::new(Session {
+                interactions: fixture.interactions,
+                position: 0,
+                unsaved: false,
+            }),
+        })
+    }
+
+    pub fn from_config<C>(
+        config: RecorderConfig<C>,
+        configure: impl FnOnce(C) -> Result<P, ConfigError>,
+    ) -> Result<Self, ConfigError> {
+        match config {
+            RecorderConfig::Record { path, inner } => Ok(Self::record(configure(inner)?, path)),
+            RecorderConfig::Replay { path } => Self::replay(path),
+        }
+    }
+
+    /// The wrapped provider, which is only present while recording.
+    pub fn inner(&self) -> Option<&P> {
+        self.inner.as_ref()
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.session().interactions.clone()
+    }
+
+    /// Performs the operation in `ctx` with `call` and records its result, or replays the next
+    /// recorded result if it was recorded for the same operation, resource and inputs.
+    pub fn intercept(
+        &self,
+        ctx: &Context,
+        inputs: Values,
+        call: impl FnOnce(&P) -> Result<Values, ProviderError>,
+    ) -> Result<Values, ProviderError> {
+        let interaction = Interaction::new(ctx, inputs);
+
+        match &self.inner {
+            Some(inner) => self.record_result(interaction, call(inner)),
+            None => self.replay_next(interaction),
+        }
+    }
+
+    /// Like `intercept`, but awaits the operation performed by `call`.
+    pub async fn intercept_async<'a, F>(
+        &'a self,
+        ctx: &Context,
+        inputs: Values,
+        call: impl FnOnce(&'a P) -> F,
+    ) -> Result<Values, ProviderError>
+    where
+        F: Future<Output = Result<Values, ProviderError>>,
+    {
+        let interaction = Interaction::new(ctx, inputs);
+
+        match &self.inner {
+            Some(inner) => {
+                let result = call(inner).await;
+                self.record_result(interaction, result)
+            }
+            None => self.replay_next(interaction),
+        }
+    }
+
+    fn record_result(
+        &self,
+        mut interaction: Interaction,
+        result: Result<Values, ProviderError>,
+    ) -> Result<Values, ProviderError> {
+        match &result {
+            Ok(outputs) => interaction.outputs = outputs.clone(),
+            Err(err) => {
+                interaction.error = Some(err.message().to_string());
+                interaction.retryable = err.is_retryable();
+                interaction.timeout = err.is_timeout();
+            }
+        }
+
+        let mut session = self.session();
+        session.interactions.push(interaction);
+        session.unsaved = true;
+
+        result
+    }
+
+    fn replay_next(&self, interaction: Interaction) -> Result<Values, ProviderError> {
+        let mut session = self.session();
+        let position = session.position;
+
+        let recorded = match session.interactions.get(position) {
+            Some(recorded) if recorded.matches(&interaction) => recorded,
+            Some(recorded) => {
+                return Err(ProviderError::new(format!(
+                    "Replay diverged at interaction {}: expected {}, got {}",
+                    position + 1,
+                    recorded.describe(),
+                    interaction.describe()
+                )))
+            }
+            None => {
+                return Err(ProviderError::new(format!(
+                    "Replay diverged at interaction {}: nothing was recorded, got {}",
+                    position + 1,
+                    interaction.describe()
+                )))
+            }
+        };
+
+        let result = match &recorded.error {
+            Some(error) if recorded.retryable => Err(ProviderError::retryable(error.clone())),
+            Some(error) if recorded.timeout => Err(ProviderError::timeout(error.clone())),
+            Some(error) => Err(ProviderError::new(error.clone())),
+            None => Ok(recorded.outputs.clone()),
+        };
+        session.position += 1;
+
+        result
+    }
+
+    /// Writes the fixture when recording. Fails when replaying if recorded operations were never
+    /// performed.
+    pub fn finish(&self) -> Result<(), ProviderError> {
+        let mut session = self.session();
+
+        if !self.is_replaying() {
+            self.save(&session.interactions)?;
+            session.unsaved = false;
+            return Ok(());
+        }
+
+        match session.interactions.get(session.position) {
+            Some(next) => Err(ProviderError::new(format!(
+                "Replay finished with {} recorded interaction(s) left, starting with {}",
+                session.interactions.len() - session.position,
+                next.describe()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> Result<(), ProviderError> {
+        let fixture = Fixture {
+            format_version: FIXTURE_FORMAT_VERSION,
+            interactions: interactions.to_vec(),
+        };
+        let json = serde_json::to_string_pretty(&fixture)
+            .map_err(|err| ProviderError::new(format!("Could not serialize fixture: {}", err)))?;
+
+        fs::write(&self.path, json).map_err(|err| {
+            ProviderError::new(format!(
+                "Could not write fixture '{}': {}",
+                self.path.display(),
+                err
+            ))
+        })
+    }
+
+    fn session(&self) -> std::sync::MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<P> Drop for Recorder<P> {
+    /// Writes a fixture that `finish` was not called for. Errors are lost here, so call `finish`
+    /// to see them.
+    fn drop(&mut self) {
+        let session = self.session();
+        if session.unsaved {
+            let _ = self.save(&session.interactions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    /// A fixture path of this process, so that concurrent test runs do not share it.
+    fn fixture_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rust_iac_recorder_{}_{}.json",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn replays_recorded_interactions() {
+        let path = fixture_path("unit");
+        let ctx = Context::new("a", "Foo", Operation::Create);
+        let inputs = Values::from([(String::from("size"), json!(1))]);
+        let outputs = Values::from([(String::from("id"), json!("abc"))]);
+
+        let recorder = Recorder::record((), &path);
+        recorder
+            .intercept(&ctx, inputs.clone(), |_| Ok(outputs.clone()))
+            .unwrap();
+        assert!(!path.exists());
+        recorder.finish().unwrap();
+
+        let replay = Recorder::<()>::replay(&path).unwrap();
+        assert!(replay.finish().is_err());
+        assert_eq!(
+            replay.intercept(&ctx, inputs, |_| unreachable!()).unwrap(),
+            outputs
+        );
+        assert!(replay.finish().is_ok());
+
+        let err = replay
+            .intercept(&ctx, Values::new(), |_| unreachable!())
+            .err()
+            .unwrap();
+        assert!(err
+            .message()
+            .starts_with("Replay diverged at interaction 2"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn returns_error_for_diverging_inputs() {
+        let path = fixture_path("diverging");
+        let ctx = Context::new("a", "Foo", Operation::Create);
+
+        Recorder::record((), &path)
+            .intercept(&ctx, Values::new(), |_| Err(ProviderError::new("boom")))
+            .unwrap_err();
+
+        let replay = Recorder::<()>::replay(&path).unwrap();
+        let inputs = Values::from([(String::from("size"), json!(2))]);
+        let err = replay
+            .intercept(&ctx, inputs, |_| unreachable!())
+            .err()
+            .unwrap();
+        assert!(err
+            .message()
+            .contains("expected create of `a` (Foo) with inputs {}"));
+
+        let replay = Recorder::<()>::replay(&path).unwrap();
+        let err = replay
+            .intercept(&ctx, Values::new(), |_| unreachable!())
+            .err()
+            .unwrap();
+        assert_eq!(err.message(), "boom");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
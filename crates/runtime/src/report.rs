@@ -0,0 +1,319 @@
+use std::fmt;
+
+use crate::{
+    Action, AttributeError, Diagnostic, Operation, Outcome, PlanError, ProviderError, StateError,
+};
+
+#[derive(Debug)]
+pub enum ApplyError {
+    State(StateError),
+    /// A saved plan could not be applied, because it no longer matches what would be done.
+    Plan(PlanError),
+    Provider {
+        resource: String,
+        operation: Operation,
+        source: ProviderError,
+        attempts: u32,
+    },
+    /// The operation did not complete in time, so the resource is recorded as uncertain.
+    Timeout {
+        resource: String,
+        operation: Operation,
+        source: ProviderError,
+    },
+    /// Some resources failed, while the others were applied or skipped as the report records.
+    Incomplete {
+        errors: Vec<ApplyError>,
+        report: Box<ApplyReport>,
+    },
+    /// The run was cancelled. Operations in progress were allowed to finish and recorded, while
+    /// the resources that were not started are skipped in the report.
+    Interrupted {
+        errors: Vec<ApplyError>,
+        report: Box<ApplyReport>,
+    },
+}
+
+impl ApplyError {
+    pub fn provider(
+        resource: &str,
+        operation: Operation,
+        source: ProviderError,
+        attempts: u32,
+    ) -> Self {
+        Self::Provider {
+            resource: resource.to_string(),
+            operation,
+            source,
+            attempts,
+        }
+    }
+
+    pub fn timeout(resource: &str, operation: Operation, source: ProviderError) -> Self {
+        Self::Timeout {
+            resource: resource.to_string(),
+            operation,
+            source,
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// Whether the run was cancelled, or this resource was not started because of it.
+    pub fn is_interrupted(&self) -> bool {
+        match self {
+            Self::Provider { source, .. } => source.is_interrupted(),
+            Self::Interrupted { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The error of every failed resource, which is just this one unless the apply was
+    /// incomplete.
+    pub fn errors(&self) -> &[ApplyError] {
+        match self {
+            Self::Incomplete { errors, .. } | Self::Interrupted { errors, .. } => errors,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// What was applied despite the failures, if the apply got that far.
+    pub fn report(&self) -> Option<&ApplyReport> {
+        match self {
+            Self::Incomplete { report, .. } | Self::Interrupted { report, .. } => Some(report),
+            _ => None,
+        }
+    }
+
+    /// Describes the error as a diagnostic for the resource it occurred on.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::State(_) | Self::Plan(_) | Self::Incomplete { .. } | Self::Interrupted { .. } => {
+                Diagnostic::error(None, self.to_string())
+            }
+            Self::Provider { resource, .. } => Diagnostic::error(Some(resource), self.to_string()),
+            Self::Timeout {
+                resource,
+                operation,
+                source,
+            } => Diagnostic::error(
+                Some(resource),
+                format!(
+                    "timed out waiting to {}, the resource is now uncertain: {}",
+                    operation, source
+                ),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::State(err) => write!(f, "{}", err),
+            Self::Plan(err) => write!(f, "{}", err),
+            Self::Provider {
+                resource,
+                operation,
+                source,
+                attempts: 1,
+            } => write!(f, "Failed to {} '{}': {}", operation, resource, source),
+            Self::Provider {
+                resource,
+                operation,
+                source,
+                attempts,
+            } => write!(
+                f,
+                "Failed to {} '{}' after {} attempts: {}",
+                operation, resource, attempts, source
+            ),
+            Self::Timeout {
+                resource,
+                operation,
+                source,
+            } => write!(
+                f,
+                "Timed out waiting to {} '{}': {}",
+                operation, resource, source
+            ),
+            Self::Incomplete { errors, .. } => {
+                for (i, err) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", err)?;
+                }
+                Ok(())
+            }
+            Self::Interrupted { errors, report } => {
+                write!(
+                    f,
+                    "Interrupted: {} succeeded, {} failed, {} not applied",
+                    report.resources.len(),
+                    report.failed.len(),
+                    report.skipped.len()
+                )?;
+                for err in errors.iter().filter(|err| !err.is_interrupted()) {
+                    write!(f, "\n{}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+impl From<StateError> for ApplyError {
+    fn from(err: StateError) -> Self {
+        Self::State(err)
+    }
+}
+
+impl From<PlanError> for ApplyError {
+    fn from(err: PlanError) -> Self {
+        Self::Plan(err)
+    }
+}
+
+impl From<AttributeError> for ApplyError {
+    fn from(err: AttributeError) -> Self {
+        Self::State(err.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceOutcome {
+    pub name: String,
+    pub resource_type: String,
+    pub action: Action,
+    /// Provider calls made, including retries. Zero when the provider was not called.
+    pub attempts: u32,
+}
+
+/// A resource whose operation failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedResource {
+    pub name: String,
+    pub resource_type: String,
+    pub reason: String,
+}
+
+/// A resource that was left alone because a resource it waits on failed or was skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedResource {
+    pub name: String,
+    pub resource_type: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApplyReport {
+    pub diagnostics: Vec<Diagnostic>,
+    /// The resources that succeeded.
+    pub resources: Vec<ResourceOutcome>,
+    pub failed: Vec<FailedResource>,
+    pub skipped: Vec<SkippedResource>,
+}
+
+impl ApplyReport {
+    pub fn new(diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            diagnostics,
+            resources: Vec::new(),
+            failed: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Records the failed and skipped resources among `outcomes`, where `resources` holds the
+    /// name and type of each resource by index. Returns the report if every resource succeeded,
+    /// and otherwise an incomplete error holding it. If the run was `cancelled`, the error is an
+    /// interrupted one, even if every resource it got to succeeded.
+    pub fn finish(
+        mut self,
+        outcomes: Vec<Outcome<ApplyError>>,
+        resources: &[(&str, &str)],
+        cancelled: bool,
+    ) -> Result<Self, ApplyError> {
+        let failed = outcomes
+            .iter()
+            .map(|outcome| matches!(outcome, Outcome::Failed(err) if !err.is_interrupted()))
+            .collect::<Vec<_>>();
+        let mut errors = Vec::new();
+
+        for (index, outcome) in outcomes.into_iter().enumerate() {
+            let (name, resource_type) = resources[index];
+            match outcome {
+                Outcome::Succeeded => {}
+                Outcome::Failed(err) if err.is_interrupted() => {
+                    self.skip(name, resource_type, "not started, the run was interrupted");
+                    errors.push(err);
+                }
+                Outcome::Failed(err) => {
+                    self.failed.push(FailedResource {
+                        name: name.to_string(),
+                        resource_type: resource_type.to_string(),
+                        reason: err.to_string(),
+                    });
+                    errors.push(err);
+                }
+                Outcome::Skipped(cause) => {
+                    let reason = format!(
+                        "waiting on '{}', which {}",
+                        resources[cause].0,
+                        if failed[cause] {
+                            "failed"
+                        } else {
+                            "was skipped"
+                        }
+                    );
+                    self.skip(name, resource_type, &reason);
+                }
+                Outcome::Cancelled => {
+                    self.skip(name, resource_type, "not started, the run was interrupted");
+                }
+            }
+        }
+
+        let report = Box::new(self);
+        if cancelled {
+            return Err(ApplyError::Interrupted { errors, report });
+        }
+
+        if errors.is_empty() {
+            return Ok(*report);
+        }
+
+        Err(ApplyError::Incomplete { errors, report })
+    }
+
+    fn skip(&mut self, name: &str, resource_type: &str, reason: &str) {
+        self.skipped.push(SkippedResource {
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Whether every resource succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+
+    pub fn push(&mut self, name: &str, resource_type: &str, action: Action, attempts: u32) {
+        self.resources.push(ResourceOutcome {
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            action,
+            attempts,
+        });
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResourceOutcome> {
+        self.resources.iter().find(|r| r.name == name)
+    }
+}
@@ -0,0 +1,405 @@
+//! A provider for JSON REST APIs. Each resource type is mapped to a [`Route`], and every
+//! operation sends the resource payload as the request body and reads outputs from the response.
+
+use std::{collections::HashMap, fmt, io::Read, sync::Arc, time::Duration};
+
+use crate::{offload, ConfigError, Context, Operation, ProviderError, RetryPolicy, Value, Values};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Patch => "PATCH",
+            Self::Delete => "DELETE",
+        }
+    }
+
+    /// Whether sending the request twice has the same effect as sending it once, so that it can
+    /// be retried when its response is lost.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, Self::Post | Self::Patch)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An HTTP method and a path template. `{field}` placeholders in the path are replaced with the
+/// resource's input or output value of that name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub method: Method,
+    pub path: String,
+}
+
+impl Endpoint {
+    pub fn new(method: Method, path: &str) -> Self {
+        Self {
+            method,
+            path: path.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub create: Endpoint,
+    pub read: Endpoint,
+    pub update: Endpoint,
+    pub delete: Endpoint,
+}
+
+impl Route {
+    /// The conventional layout of a collection: `POST path` to create and
+    /// `GET`/`PUT`/`DELETE path/{id}` for the rest.
+    pub fn collection(path: &str) -> Self {
+        let path = path.trim_end_matches('/');
+        let item = format!("{}/{{id}}", path);
+
+        Self {
+            create: Endpoint::new(Method::Post, path),
+            read: Endpoint::new(Method::Get, &item),
+            update: Endpoint::new(Method::Put, &item),
+            delete: Endpoint::new(Method::Delete, &item),
+        }
+    }
+
+    pub fn create(mut self, endpoint: Endpoint) -> Self {
+        self.create = endpoint;
+        self
+    }
+
+    pub fn read(mut self, endpoint: Endpoint) -> Self {
+        self.read = endpoint;
+        self
+    }
+
+    pub fn update(mut self, endpoint: Endpoint) -> Self {
+        self.update = endpoint;
+        self
+    }
+
+    pub fn delete(mut self, endpoint: Endpoint) -> Self {
+        self.delete = endpoint;
+        self
+    }
+
+    fn endpoint(&self, operation: Operation) -> &Endpoint {
+        match operation {
+            Operation::Create => &self.create,
+            Operation::Read => &self.read,
+            Operation::Update => &self.update,
+            Operation::Delete => &self.delete,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestConfig {
+    pub base_url: String,
+    pub routes: HashMap<String, Route>,
+    pub headers: Vec<(String, String)>,
+    pub timeout: Option<Duration>,
+    pub retry_policy: RetryPolicy,
+}
+
+impl RestConfig {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            routes: HashMap::new(),
+            headers: Vec::new(),
+            timeout: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Maps the resource type named `resource_type` to `route`.
+    pub fn route(mut self, resource_type: &str, route: Route) -> Self {
+        self.routes.insert(resource_type.to_string(), route);
+        self
+    }
+
+    /// Adds a header sent with every request, e.g. for authentication.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries throttled requests, gateway errors and failed connections with `policy`. A `POST`
+    /// or `PATCH` that fails after connecting is not retried, as it may have taken effect.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+}
+
+/// Implements the `Provider` and `AsyncProvider` traits of a `#[provider(rest)]` module. Clones
+/// share the config and connection pool.
+#[derive(Clone)]
+pub struct RestProvider {
+    config: Arc<RestConfig>,
+    agent: ureq::Agent,
+}
+
+impl RestProvider {
+    pub fn new(config: RestConfig) -> Result<Self, ConfigError> {
+        if !config.base_url.starts_with("http://") && !config.base_url.starts_with("https://") {
+            return Err(ConfigError::invalid("base_url", "expected an http(s) url"));
+        }
+
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(timeout) = config.timeout {
+            agent = agent.timeout(timeout);
+        }
+
+        Ok(Self {
+            config: Arc::new(config),
+            agent: agent.build(),
+        })
+    }
+
+    pub fn config(&self) -> &RestConfig {
+        &self.config
+    }
+
+    /// Like `send`, but runs the blocking request on a worker thread so that it does not block the
+    /// executor.
+    pub async fn send_async(
+        &self,
+        ctx: &Context,
+        values: Values,
+        body: Option<Value>,
+    ) -> Result<Values, ProviderError> {
+        let (provider, ctx) = (self.clone(), ctx.clone());
+        offload(move || provider.send(&ctx, &values, body.as_ref())).await
+    }
+
+    /// Sends the request routed for `ctx`, filling path placeholders from `values`. Returns the
+    /// fields of the JSON object in the response, or no values for an empty response.
+    pub fn send(
+        &self,
+        ctx: &Context,
+        values: &Values,
+        body: Option<&Value>,
+    ) -> Result<Values, ProviderError> {
+        let route = self.config.routes.get(ctx.resource_type).ok_or_else(|| {
+            ProviderError::new(format!(
+                "No route configured for resource type `{}`",
+                ctx.resource_type
+            ))
+        })?;
+        let endpoint = route.endpoint(ctx.operation);
+        let url = format!(
+            "{}{}",
+            self.config.base_url,
+            expand_path(&endpoint.path, values)?
+        );
+
+        let mut request = self.agent.request(endpoint.method.as_str(), &url);
+        if let Some(remaining) = ctx.remaining() {
+            request = request.timeout(remaining);
+        }
+        for (name, value) in &self.config.headers {
+            request = request.set(name, value);
+        }
+
+        let result = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None => request.call(),
+        };
+
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                let message = format!(
+                    "{} {} returned status {}: {}",
+                    endpoint.method, url, status, body
+                );
+
+                // Throttling and gateway errors are transient, anything else is the request.
+                return Err(match status {
+                    404 => ProviderError::not_found(message),
+                    429 | 502 | 503 | 504 => ProviderError::retryable(message),
+                    _ => ProviderError::new(message),
+                });
+            }
+            Err(err) if ctx.is_expired() => {
+                return Err(ProviderError::timeout(format!(
+                    "{} {} did not complete before the deadline: {}",
+                    endpoint.method, url, err
+                )))
+            }
+            Err(err) if endpoint.method.is_idempotent() || !was_sent(&err) => {
+                return Err(ProviderError::retryable(format!(
+                    "{} {} failed: {}",
+                    endpoint.method, url, err
+                )))
+            }
+            // The server may have acted on the request, so sending it again could e.g. create a
+            // duplicate. Reporting it as timed out records the resource as uncertain instead.
+            Err(err) => {
+                return Err(ProviderError::timeout(format!(
+                    "{} {} failed after the request may have been received: {}",
+                    endpoint.method, url, err
+                )))
+            }
+        };
+
+        let mut body = String::new();
+        response
+            .into_reader()
+            .read_to_string(&mut body)
+            .map_err(|err| ProviderError::new(format!("Could not read response: {}", err)))?;
+
+        parse_response(&body)
+    }
+}
+
+/// Whether the request may have reached the server before `err`, rather than failing to connect.
+fn was_sent(err: &ureq::Error) -> bool {
+    !matches!(
+        err.kind(),
+        ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed
+    )
+}
+
+fn expand_path(template: &str, values: &Values) -> Result<String, ProviderError> {
+    let mut path = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            ProviderError::new(format!("Unclosed placeholder in path `{}`", template))
+        })? + start;
+        let field = &rest[start + 1..end];
+        let value = match values.get(field) {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Null) | None => {
+                return Err(ProviderError::new(format!(
+                    "Path `{}` references `{}`, which has no value",
+                    template, field
+                )))
+            }
+            Some(value) => value.to_string(),
+        };
+
+        path.push_str(&rest[..start]);
+        path.push_str(&encode_segment(&value));
+        rest = &rest[end + 1..];
+    }
+
+    path.push_str(rest);
+    Ok(path)
+}
+
+fn encode_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn parse_response(body: &str) -> Result<Values, ProviderError> {
+    if body.trim().is_empty() {
+        return Ok(Values::new());
+    }
+
+    match serde_json::from_str(body) {
+        Ok(Value::Object(fields)) => Ok(fields.into_iter().collect()),
+        Ok(_) => Err(ProviderError::new("Expected a JSON object in the response")),
+        Err(err) => Err(ProviderError::new(format!(
+            "Invalid JSON response: {}",
+            err
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn expands_path_placeholders() {
+        let values = Values::from([
+            (String::from("id"), json!(7)),
+            (String::from("name"), json!("a b/c")),
+        ]);
+
+        assert_eq!(
+            expand_path("/items/{id}/tags/{name}", &values).unwrap(),
+            "/items/7/tags/a%20b%2Fc"
+        );
+        assert!(expand_path("/items/{missing}", &values).is_err());
+        assert!(expand_path("/items/{id", &values).is_err());
+    }
+
+    #[test]
+    fn parses_response_fields() {
+        assert_eq!(
+            parse_response(r#"{"id": 7}"#).unwrap(),
+            Values::from([(String::from("id"), json!(7))])
+        );
+        assert!(parse_response("").unwrap().is_empty());
+        assert!(parse_response("[1]").is_err());
+    }
+
+    #[test]
+    fn retries_only_requests_that_are_safe_to_resend() {
+        // Nothing listens on the port once the listener is dropped, so connecting fails.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = RestConfig::new(&format!("http://127.0.0.1:{}", port))
+            .route("Item", Route::collection("/items"))
+            .retry_policy(RetryPolicy::none());
+        let provider = RestProvider::new(config).unwrap();
+        let values = Values::from([(String::from("id"), json!(7))]);
+
+        let err = provider
+            .send(&Context::new("a", "Item", Operation::Create), &values, None)
+            .unwrap_err();
+        assert!(err.is_retryable());
+
+        assert!(Method::Put.is_idempotent() && Method::Delete.is_idempotent());
+        assert!(!Method::Post.is_idempotent() && !Method::Patch.is_idempotent());
+    }
+
+    #[test]
+    fn rejects_non_http_base_url() {
+        assert!(matches!(
+            RestProvider::new(RestConfig::new("ftp://localhost")),
+            Err(ConfigError::Invalid { .. })
+        ));
+    }
+}
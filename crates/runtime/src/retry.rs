@@ -0,0 +1,325 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{
+    timeout::{backoff, backoff_async, within},
+    Context, ProviderError,
+};
+
+/// How often and how long to retry an operation that failed with a retryable error. Fatal errors
+/// are never retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, backing off from 200ms up to 5s with jitter.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Makes a single attempt.
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    /// Waits `initial` after the first failed attempt, growing by the multiplier up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Randomizes each backoff between half and all of its length, so that concurrent retries
+    /// spread out.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Stops retrying once another backoff would end after `timeout` since the first attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The backoff after `attempt` failed attempts, before jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        self.initial_backoff
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_backoff)
+    }
+
+    /// How long to wait before retrying after `attempts` attempts started at `start`, the last of
+    /// which failed with `err`, or `None` if it should not be retried.
+    pub fn backoff_after(
+        &self,
+        err: &ProviderError,
+        attempts: u32,
+        start: Instant,
+    ) -> Option<Duration> {
+        if !err.is_retryable() || attempts >= self.max_attempts {
+            return None;
+        }
+
+        let mut delay = self.delay(attempts);
+        if self.jitter {
+            delay = delay.mul_f64(0.5 + random_fraction() / 2.0);
+        }
+
+        match self.timeout {
+            Some(timeout) if start.elapsed() + delay >= timeout => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Calls `call` until it succeeds, fails with a fatal error or the policy is exhausted.
+    /// Returns the last result and the number of attempts made.
+    ///
+    /// An attempt is not started once `ctx` is cancelled or past its deadline. Backoffs end early
+    /// at the deadline, and as soon as the run is cancelled, with the matching error.
+    pub fn run<T>(
+        &self,
+        ctx: &Context,
+        mut call: impl FnMut() -> Result<T, ProviderError>,
+    ) -> (Result<T, ProviderError>, u32) {
+        let start = Instant::now();
+        let mut attempts = 0;
+
+        loop {
+            attempts += 1;
+
+            let result = ctx.check_deadline().and_then(|()| call());
+
+            match result {
+                Err(err) => match self.backoff_after(&err, attempts, start) {
+                    Some(delay) => {
+                        if let Err(err) = backoff(ctx, delay) {
+                            return (Err(err), attempts);
+                        }
+                    }
+                    None => return (Err(err), attempts),
+                },
+                result => return (result, attempts),
+            }
+        }
+    }
+
+    /// Like `run`, but awaits each attempt. One still pending at the deadline is dropped as a
+    /// timeout.
+    pub async fn run_async<T, F>(
+        &self,
+        ctx: &Context,
+        mut call: impl FnMut() -> F,
+    ) -> (Result<T, ProviderError>, u32)
+    where
+        F: Future<Output = Result<T, ProviderError>>,
+    {
+        let mut attempts = self.attempts(ctx);
+
+        loop {
+            if let Some(done) = attempts.run(call()).await {
+                return done;
+            }
+        }
+    }
+
+    /// The attempts of one async operation, for callers whose attempts borrow what the previous
+    /// one did, e.g. the resource being created, which `run_async` cannot lend to each call.
+    pub fn attempts<'a>(&'a self, ctx: &'a Context) -> Attempts<'a> {
+        Attempts {
+            policy: self,
+            ctx,
+            start: Instant::now(),
+            count: 0,
+        }
+    }
+}
+
+/// Runs the attempts of an async operation under a `RetryPolicy`.
+#[derive(Debug)]
+pub struct Attempts<'a> {
+    policy: &'a RetryPolicy,
+    ctx: &'a Context,
+    start: Instant,
+    count: u32,
+}
+
+impl Attempts<'_> {
+    /// Makes the next attempt, which is not started once the context is cancelled or past its
+    /// deadline, and is dropped as a timeout if still pending at the deadline. Returns `None`
+    /// after waiting out the backoff if it should be retried, or else its result and the number
+    /// of attempts made.
+    pub async fn run<T>(
+        &mut self,
+        attempt: impl Future<Output = Result<T, ProviderError>>,
+    ) -> Option<(Result<T, ProviderError>, u32)> {
+        self.count += 1;
+
+        let result = match self.ctx.check_deadline() {
+            Ok(()) => within(self.ctx, attempt).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Err(err) => match self.policy.backoff_after(&err, self.count, self.start) {
+                Some(delay) => match backoff_async(self.ctx, delay).await {
+                    Ok(()) => None,
+                    Err(err) => Some((Err(err), self.count)),
+                },
+                None => Some((Err(err), self.count)),
+            },
+            result => Some((result, self.count)),
+        }
+    }
+}
+
+/// A number in `[0, 1)` that is random enough to spread out retries.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CancellationToken, Operation};
+    use std::thread;
+
+    fn ctx() -> Context {
+        Context::new("a", "Foo", Operation::Create)
+    }
+
+    fn fast(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).backoff(Duration::from_millis(1), Duration::from_millis(1))
+    }
+
+    #[test]
+    fn grows_backoff_up_to_max() {
+        let policy =
+            RetryPolicy::new(5).backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn retries_retryable_errors_only() {
+        let mut calls = 0;
+        let (result, attempts) = fast(3).run(&ctx(), || {
+            calls += 1;
+            Err::<(), _>(ProviderError::retryable("throttled"))
+        });
+        assert!(result.is_err());
+        assert_eq!((calls, attempts), (3, 3));
+
+        let (result, attempts) = fast(3).run(&ctx(), || Err::<(), _>(ProviderError::new("denied")));
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+
+        let mut calls = 0;
+        let (result, attempts) = fast(3).run(&ctx(), || {
+            calls += 1;
+            if calls < 2 {
+                Err(ProviderError::retryable("throttled"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(2));
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn stops_retrying_at_timeout() {
+        let policy = RetryPolicy::new(10)
+            .backoff(Duration::from_millis(20), Duration::from_millis(20))
+            .jitter(false)
+            .timeout(Duration::from_millis(50));
+
+        let (_, attempts) = policy.run(&ctx(), || {
+            Err::<(), _>(ProviderError::retryable("throttled"))
+        });
+
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn stops_backing_off_at_the_deadline() {
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_secs(30), Duration::from_secs(30))
+            .jitter(false);
+        let ctx = ctx().with_deadline(Instant::now() + Duration::from_millis(20));
+
+        let start = Instant::now();
+        let (result, attempts) =
+            policy.run(&ctx, || Err::<(), _>(ProviderError::retryable("throttled")));
+
+        assert!(result.unwrap_err().is_timeout());
+        assert_eq!(attempts, 1);
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[test]
+    fn stops_backing_off_when_cancelled() {
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_secs(30), Duration::from_secs(30))
+            .jitter(false);
+        let cancellation = CancellationToken::new();
+        let ctx = ctx().with_cancellation(cancellation.clone());
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            cancellation.cancel();
+        });
+        let start = Instant::now();
+        let (result, attempts) =
+            policy.run(&ctx, || Err::<(), _>(ProviderError::retryable("throttled")));
+        canceller.join().unwrap();
+
+        assert!(result.unwrap_err().is_interrupted());
+        assert_eq!(attempts, 1);
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,296 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeError, Diagnostic, Values};
+
+const STATE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    UnsupportedFormat(u32),
+    InvalidValue(AttributeError),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Could not access state file: {}", err),
+            Self::Parse(err) => write!(f, "Invalid state file: {}", err),
+            Self::UnsupportedFormat(version) => {
+                write!(f, "Unsupported state file format version {}", version)
+            }
+            Self::InvalidValue(err) => write!(f, "Invalid recorded value: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<io::Error> for StateError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<AttributeError> for StateError {
+    fn from(err: AttributeError) -> Self {
+        Self::InvalidValue(err)
+    }
+}
+
+impl From<serde_json::Error> for StateError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub version: String,
+}
+
+impl ProviderInfo {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    /// Versions are compatible when they share a major version, or a minor version while still
+    /// below `1.0.0`. Unparseable versions are only compatible with themselves.
+    pub fn is_compatible_with(&self, version: &str) -> bool {
+        match (parse_version(&self.version), parse_version(version)) {
+            (Some((0, minor, _)), Some((0, other_minor, _))) => minor == other_minor,
+            (Some((major, _, _)), Some((other_major, _, _))) => major == other_major,
+            _ => self.version == version,
+        }
+    }
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => {
+            Some((major, minor, patch))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceStatus {
+    #[default]
+    Ready,
+    /// An operation on the resource timed out, so it may not match its recorded values.
+    Uncertain,
+}
+
+impl ResourceStatus {
+    fn is_ready(&self) -> bool {
+        *self == Self::Ready
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRecord {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub provider: ProviderInfo,
+    #[serde(default, skip_serializing_if = "Values::is_empty")]
+    pub inputs: Values,
+    #[serde(default, skip_serializing_if = "Values::is_empty")]
+    pub outputs: Values,
+    #[serde(default, skip_serializing_if = "ResourceStatus::is_ready")]
+    pub status: ResourceStatus,
+}
+
+impl ResourceRecord {
+    pub fn new(name: &str, resource_type: &str, provider: ProviderInfo) -> Self {
+        Self {
+            name: name.to_string(),
+            resource_type: resource_type.to_string(),
+            provider,
+            inputs: Values::new(),
+            outputs: Values::new(),
+            status: ResourceStatus::Ready,
+        }
+    }
+
+    pub fn with_values(mut self, inputs: Values, outputs: Values) -> Self {
+        self.inputs = inputs;
+        self.outputs = outputs;
+        self
+    }
+
+    pub fn with_status(mut self, status: ResourceStatus) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Resources recorded by previous applies, persisted as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateFile {
+    format_version: u32,
+    resources: Vec<ResourceRecord>,
+}
+
+impl Default for StateFile {
+    fn default() -> Self {
+        Self {
+            format_version: STATE_FORMAT_VERSION,
+            resources: Vec::new(),
+        }
+    }
+}
+
+impl StateFile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the state file at `path`, or an empty state if it does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, StateError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Self::from_json(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), StateError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, StateError> {
+        let state: Self = serde_json::from_str(json)?;
+
+        if state.format_version != STATE_FORMAT_VERSION {
+            return Err(StateError::UnsupportedFormat(state.format_version));
+        }
+
+        Ok(state)
+    }
+
+    pub fn to_json(&self) -> Result<String, StateError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn resources(&self) -> &[ResourceRecord] {
+        &self.resources
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResourceRecord> {
+        self.resources.iter().find(|r| r.name == name)
+    }
+
+    /// Inserts `record`, replacing any previous record with the same name.
+    pub fn record(&mut self, record: ResourceRecord) {
+        match self.resources.iter_mut().find(|r| r.name == record.name) {
+            Some(existing) => *existing = record,
+            None => self.resources.push(record),
+        }
+    }
+
+    /// Marks the resource named `name` as uncertain, if it is recorded.
+    pub fn mark_uncertain(&mut self, name: &str) {
+        if let Some(record) = self.resources.iter_mut().find(|r| r.name == name) {
+            record.status = ResourceStatus::Uncertain;
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ResourceRecord> {
+        let idx = self.resources.iter().position(|r| r.name == name)?;
+        Some(self.resources.remove(idx))
+    }
+
+    /// Warns about every resource recorded by a version of `provider` incompatible with `version`.
+    pub fn check_provider(&self, provider: &str, version: &str) -> Vec<Diagnostic> {
+        self.resources
+            .iter()
+            .filter(|r| r.provider.name == provider && !r.provider.is_compatible_with(version))
+            .map(|r| {
+                Diagnostic::warning(
+                    Some(&r.name),
+                    format!(
+                        "recorded by provider `{}` version {}, which is incompatible with version {}",
+                        provider, r.provider.version, version
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn provider_versions_are_compatible_within_major_version() {
+        let provider = ProviderInfo::new("acme", "1.4.0");
+
+        assert!(provider.is_compatible_with("1.9.2"));
+        assert!(!provider.is_compatible_with("2.0.0"));
+        assert!(ProviderInfo::new("acme", "0.3.1").is_compatible_with("0.3.0"));
+        assert!(!ProviderInfo::new("acme", "0.3.1").is_compatible_with("0.4.0"));
+    }
+
+    #[test]
+    fn record_replaces_resource_with_same_name() {
+        let provider = ProviderInfo::new("acme", "1.4.0");
+        let mut state = StateFile::new();
+
+        state.record(ResourceRecord::new("a", "Foo", provider.clone()));
+        state.record(ResourceRecord::new("a", "Bar", provider));
+
+        assert_eq!(state.resources().len(), 1);
+        assert_eq!(state.get("a").unwrap().resource_type, "Bar");
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut state = StateFile::new();
+        state.record(
+            ResourceRecord::new("a", "Foo", ProviderInfo::new("acme", "1.4.0")).with_values(
+                Values::from([(String::from("size"), serde_json::json!(3))]),
+                Values::from([(String::from("id"), serde_json::json!("abc"))]),
+            ),
+        );
+
+        let json = state.to_json().unwrap();
+
+        assert_eq!(StateFile::from_json(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn warns_about_incompatible_provider_versions() {
+        let mut state = StateFile::new();
+        state.record(ResourceRecord::new(
+            "a",
+            "Foo",
+            ProviderInfo::new("acme", "1.4.0"),
+        ));
+        state.record(ResourceRecord::new(
+            "b",
+            "Foo",
+            ProviderInfo::new("other", "1.4.0"),
+        ));
+
+        assert!(state.check_provider("acme", "1.5.0").is_empty());
+
+        let diagnostics = state.check_provider("acme", "2.0.0");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].resource.as_deref(), Some("a"));
+    }
+}
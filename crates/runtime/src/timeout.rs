@@ -0,0 +1,131 @@
+use std::{
+    future::{self, Future},
+    pin::{pin, Pin},
+    task::Poll,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{sleep, Context, Operation, ProviderError, Sleep};
+
+/// How often a call waiting on its deadline checks whether the run was cancelled, which brings the
+/// deadline forward to the end of the grace period.
+const CANCELLATION_CHECK: Duration = Duration::from_millis(50);
+
+/// How long the State waits for each kind of operation on a resource. Reads have no timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    create: Duration,
+    update: Duration,
+    delete: Duration,
+}
+
+impl Default for Timeouts {
+    /// Twenty minutes for every operation.
+    fn default() -> Self {
+        let timeout = Duration::from_secs(20 * 60);
+
+        Self {
+            create: timeout,
+            update: timeout,
+            delete: timeout,
+        }
+    }
+}
+
+impl Timeouts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(mut self, timeout: Duration) -> Self {
+        self.create = timeout;
+        self
+    }
+
+    pub fn update(mut self, timeout: Duration) -> Self {
+        self.update = timeout;
+        self
+    }
+
+    pub fn delete(mut self, timeout: Duration) -> Self {
+        self.delete = timeout;
+        self
+    }
+
+    pub fn get(&self, operation: Operation) -> Option<Duration> {
+        match operation {
+            Operation::Create => Some(self.create),
+            Operation::Update => Some(self.update),
+            Operation::Delete => Some(self.delete),
+            Operation::Read => None,
+        }
+    }
+}
+
+/// Awaits `call` until the deadline in `ctx`, after which it is dropped and reported as a timeout.
+pub(crate) async fn within<T>(
+    ctx: &Context,
+    call: impl Future<Output = Result<T, ProviderError>>,
+) -> Result<T, ProviderError> {
+    let mut call = pin!(call);
+    let mut timer: Option<Sleep> = None;
+
+    future::poll_fn(|cx| {
+        if let Poll::Ready(result) = call.as_mut().poll(cx) {
+            return Poll::Ready(result);
+        }
+
+        loop {
+            if ctx.is_expired() {
+                return Poll::Ready(Err(ctx.timed_out()));
+            }
+
+            let check = timer.get_or_insert_with(|| sleep(wait_time(ctx)));
+            if Pin::new(check).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            timer = None;
+        }
+    })
+    .await
+}
+
+/// Waits `delay` before retrying an operation, but fails as soon as the run is cancelled or the
+/// deadline in `ctx` passes.
+pub(crate) fn backoff(ctx: &Context, delay: Duration) -> Result<(), ProviderError> {
+    let delay = ctx
+        .remaining()
+        .map_or(delay, |remaining| remaining.min(delay));
+
+    match &ctx.cancellation {
+        Some(token) => {
+            token.wait(delay);
+        }
+        None => thread::sleep(delay),
+    }
+
+    ctx.check_deadline()
+}
+
+/// Like `backoff`, but awaits the delay, checking for cancellation as `within` does.
+pub(crate) async fn backoff_async(ctx: &Context, delay: Duration) -> Result<(), ProviderError> {
+    let end = Instant::now() + delay;
+
+    loop {
+        ctx.check_deadline()?;
+
+        let left = end.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(());
+        }
+        sleep(left.min(wait_time(ctx))).await;
+    }
+}
+
+/// How long to wait before checking the deadline in `ctx` again.
+fn wait_time(ctx: &Context) -> Duration {
+    ctx.remaining().map_or(CANCELLATION_CHECK, |remaining| {
+        remaining.min(CANCELLATION_CHECK)
+    })
+}
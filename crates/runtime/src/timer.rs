@@ -0,0 +1,177 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Condvar, Mutex, MutexGuard, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A waker waiting for a deadline, identified by the registration that added it.
+struct Entry {
+    registration: u64,
+    deadline: Instant,
+    waker: Waker,
+}
+
+/// Wakers waiting for a deadline, served by a single background thread so that sleeping does not
+/// depend on a particular executor.
+struct Timer {
+    waiting: Mutex<Vec<Entry>>,
+    changed: Condvar,
+    next_registration: AtomicU64,
+}
+
+impl Timer {
+    fn get() -> &'static Self {
+        static TIMER: OnceLock<Timer> = OnceLock::new();
+
+        TIMER.get_or_init(|| {
+            thread::Builder::new()
+                .name(String::from("runtime-timer"))
+                .spawn(|| Timer::get().run())
+                .expect("could not start the timer thread");
+
+            Self {
+                waiting: Mutex::new(Vec::new()),
+                changed: Condvar::new(),
+                next_registration: AtomicU64::new(0),
+            }
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Entry>> {
+        self.waiting.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Wakes `waker` at `deadline`, replacing the waker of `registration` if it is still waiting,
+    /// and returns the registration to update next time.
+    fn register(&self, registration: Option<u64>, deadline: Instant, waker: &Waker) -> u64 {
+        let mut waiting = self.lock();
+
+        if let Some(entry) = waiting
+            .iter_mut()
+            .find(|entry| Some(entry.registration) == registration)
+        {
+            if !entry.waker.will_wake(waker) {
+                entry.waker = waker.clone();
+            }
+            return entry.registration;
+        }
+
+        let registration = self.next_registration.fetch_add(1, Ordering::Relaxed);
+        waiting.push(Entry {
+            registration,
+            deadline,
+            waker: waker.clone(),
+        });
+        self.changed.notify_one();
+
+        registration
+    }
+
+    fn deregister(&self, registration: u64) {
+        self.lock()
+            .retain(|entry| entry.registration != registration);
+    }
+
+    fn run(&self) {
+        let mut waiting = self.lock();
+
+        loop {
+            let now = Instant::now();
+            waiting.retain(|entry| {
+                if entry.deadline <= now {
+                    entry.waker.wake_by_ref();
+                }
+                entry.deadline > now
+            });
+
+            waiting = match waiting.iter().map(|entry| entry.deadline).min() {
+                Some(next) => {
+                    self.changed
+                        .wait_timeout(waiting, next - now)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(waiting)
+                    .unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+}
+
+/// A future that completes after `duration`, on any executor.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+        registration: None,
+    }
+}
+
+pub struct Sleep {
+    deadline: Instant,
+    registration: Option<u64>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let registration = Timer::get().register(self.registration, self.deadline, cx.waker());
+        self.registration = Some(registration);
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(registration) = self.registration {
+            Timer::get().deregister(registration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::task::Wake;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: std::sync::Arc<Self>) {}
+    }
+
+    #[test]
+    fn keeps_one_registration_per_sleep() {
+        let waker = Waker::from(std::sync::Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut sleep = Box::pin(sleep(Duration::from_secs(60)));
+        let waiting = |registration| {
+            Timer::get()
+                .lock()
+                .iter()
+                .filter(|entry| Some(entry.registration) == registration)
+                .count()
+        };
+
+        for _ in 0..3 {
+            assert!(sleep.as_mut().poll(&mut cx).is_pending());
+        }
+        let registration = sleep.registration;
+        assert_eq!(waiting(registration), 1);
+
+        drop(sleep);
+        assert_eq!(waiting(registration), 0);
+    }
+}
@@ -1,5 +1,5 @@
 use helpers::ItemAttrs;
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::{
     braced,
@@ -37,6 +37,38 @@ impl ItemResource {
 
         dependencies
     }
+
+    /// Every one of `names` used as `name.…` anywhere in the field values, including inside
+    /// macro calls such as `format!`.
+    pub(crate) fn get_references(&self, names: &[Ident]) -> Vec<Ident> {
+        fn scan(tokens: TokenStream, names: &[Ident], references: &mut Vec<Ident>) {
+            let mut tokens = tokens.into_iter().peekable();
+
+            while let Some(token) = tokens.next() {
+                match token {
+                    TokenTree::Group(group) => scan(group.stream(), names, references),
+                    TokenTree::Ident(ident) => {
+                        let is_access = matches!(
+                            tokens.peek(),
+                            Some(TokenTree::Punct(punct)) if punct.as_char() == '.'
+                        );
+
+                        if is_access && names.contains(&ident) && !references.contains(&ident) {
+                            references.push(ident);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut references = Vec::new();
+        for field in &self.fields {
+            scan(field.expr.to_token_stream(), names, &mut references);
+        }
+
+        references
+    }
 }
 
 impl Parse for ItemResource {
@@ -156,4 +188,21 @@ mod test {
         let dependencies = resource.get_dependencies();
         assert_eq!(dependencies.len(), 2);
     }
+
+    #[test]
+    fn resource_gives_references_inside_macros() {
+        let dir = Ident::new("dir", Span::call_site());
+        let other = Ident::new("other", Span::call_site());
+
+        let stream = quote! {
+            Foo {
+                path: format!("{}/{}", #dir.get_path(), local.len()),
+                name: String::from("dir"),
+            };
+        };
+
+        let resource = parse2::<ItemResource>(stream).unwrap();
+
+        assert_eq!(resource.get_references(&[dir.clone(), other]), vec![dir]);
+    }
 }
@@ -4,12 +4,15 @@ mod state_attribute;
 mod state_definition;
 
 use items::item_state::ItemState;
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
 use state_definition::StateDefintion;
 use syn::parse2;
 
 #[proc_macro]
 pub fn state(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let item_state = match parse2::<ItemState>(input.into()) {
+    let input = TokenStream::from(input);
+    let source = normalized(input.clone());
+    let item_state = match parse2::<ItemState>(input) {
         Ok(item_state) => item_state,
         Err(err) => {
             return err.to_compile_error().into();
@@ -21,5 +24,71 @@ pub fn state(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         Err(err) => return err.to_compile_error().into(),
     };
 
-    def.expand().into()
+    def.expand(&source).into()
+}
+
+/// The tokens of `input` separated by single spaces, with joint punctuation such as `::` kept
+/// together. Unlike `TokenStream::to_string`, this does not depend on how the compiler formats
+/// tokens, so the definition fingerprint of a saved plan survives a toolchain upgrade.
+fn normalized(input: TokenStream) -> String {
+    let mut source = String::new();
+    let mut joint = false;
+
+    for token in input {
+        if !source.is_empty() && !joint {
+            source.push(' ');
+        }
+        joint = false;
+
+        match token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                let inner = normalized(group.stream());
+                source += &[open, inner.as_str(), close]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+            TokenTree::Ident(ident) => source += &ident.to_string(),
+            TokenTree::Literal(literal) => source += &literal.to_string(),
+            TokenTree::Punct(punct) => {
+                source.push(punct.as_char());
+                joint = punct.spacing() == Spacing::Joint;
+            }
+        }
+    }
+
+    source
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalized_source_ignores_formatting() {
+        let compact = "#[resource(name=db)]Database{zone:x::zone(),sizes:vec![1,2]}";
+        let spaced = "
+            #[resource(name = db)]
+            Database {
+                zone: x::zone(),
+                sizes: vec![1, 2]
+            }
+        ";
+
+        assert_eq!(
+            normalized(compact.parse().unwrap()),
+            "# [ resource ( name = db ) ] Database { zone : x :: zone ( ) , sizes : vec ! [ 1 , 2 ] }"
+        );
+        assert_eq!(
+            normalized(spaced.parse().unwrap()),
+            normalized(compact.parse().unwrap())
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::{items::item_resource::ItemResource, state_attribute::ResourceField};
 use quote::quote;
 use syn::Ident;
@@ -6,6 +8,9 @@ use syn::Ident;
 pub(crate) struct Resource {
     pub(crate) item_resource: ItemResource,
     pub(crate) name_val: Ident,
+    pub(crate) timeouts: Vec<(Ident, Duration)>,
+    /// Other resources of the state referenced in the field values.
+    pub(crate) references: Vec<Ident>,
 }
 
 impl Resource {
@@ -15,18 +20,53 @@ impl Resource {
         Self {
             item_resource,
             name_val,
+            timeouts: resource_field.timeouts,
+            references: Vec::new(),
         }
     }
 
     pub(crate) fn expand_instantiation(&self) -> proc_macro2::TokenStream {
         let name = &self.name_val;
         let struct_name = &self.item_resource.ident;
-        let fields = self.item_resource.fields.iter().map(|f| f.expr.clone());
+        let field_name = self.item_resource.fields.iter().map(|f| &f.member);
+        let field_value = self.item_resource.fields.iter().map(|f| &f.expr);
+
+        quote! {
+            let mut #name: #struct_name = #struct_name::builder()
+                #(.#field_name(#field_value))*
+                .build();
+        }
+    }
+
+    pub(crate) fn expand_input_assertion(&self) -> proc_macro2::TokenStream {
+        let struct_name = &self.item_resource.ident;
+        let field_name = self.item_resource.fields.iter().map(|f| {
+            let member = &f.member;
+            quote! { #member }.to_string()
+        });
+
+        quote! {
+            const _: () = #struct_name::__assert_required_inputs(&[#(#field_name),*]);
+        }
+    }
+
+    /// The resource type's default timeouts with the overrides from the resource attribute, for
+    /// the resource bound by value or, with `by_ref`, by reference.
+    pub(crate) fn expand_timeouts(&self, by_ref: bool) -> proc_macro2::TokenStream {
+        let name = &self.name_val;
+        let receiver = if by_ref {
+            quote! { #name }
+        } else {
+            quote! { &#name }
+        };
+        let struct_name = &self.item_resource.ident;
+        let resource_trait_name = helpers::resource_trait_name();
+        let operation = self.timeouts.iter().map(|(op, _)| op);
+        let millis = self.timeouts.iter().map(|(_, d)| d.as_millis() as u64);
 
         quote! {
-            let #name: #struct_name = #struct_name::new(
-                #(#fields,)*
-            );
+            <#struct_name as #resource_trait_name>::timeouts(#receiver)
+                #(.#operation(::std::time::Duration::from_millis(#millis)))*
         }
     }
 
@@ -39,7 +79,14 @@ impl Resource {
     }
 
     pub(crate) fn get_dependencies(&self) -> Vec<Ident> {
-        self.item_resource.get_dependencies()
+        let mut dependencies = self.item_resource.get_dependencies();
+        for reference in &self.references {
+            if !dependencies.contains(reference) {
+                dependencies.push(reference.clone());
+            }
+        }
+
+        dependencies
     }
 
     pub(crate) fn name(&self) -> String {
@@ -1,7 +1,8 @@
+use std::time::Duration;
+
 use syn::{
     parse::{Parse, ParseStream},
-    punctuated::Punctuated,
-    Ident, Token,
+    Ident, LitStr, Token,
 };
 
 pub(crate) enum StateAttribute {
@@ -10,6 +11,8 @@ pub(crate) enum StateAttribute {
 
 pub(crate) struct ResourceField {
     pub(crate) name_val: Ident,
+    /// Operation timeouts overriding the resource type's defaults, e.g. `timeouts(create = "10m")`.
+    pub(crate) timeouts: Vec<(Ident, Duration)>,
 }
 
 impl Parse for StateAttribute {
@@ -34,27 +37,128 @@ impl Parse for ResourceField {
         let content;
         syn::parenthesized!(content in input);
 
-        let metaitem: Punctuated<Ident, Token![=]> =
-            content.parse_terminated(Ident::parse, Token![=])?;
+        let mut name_val = None;
+        let mut timeouts = Vec::new();
+
+        while !content.is_empty() {
+            let key = content.parse::<Ident>()?;
+
+            if key == "timeouts" {
+                let inner;
+                syn::parenthesized!(inner in content);
+                timeouts = parse_timeouts(&inner)?;
+            } else if key == "name" {
+                content.parse::<Token![=]>()?;
+                let value = content.parse::<Ident>().map_err(|_| {
+                    syn::Error::new(
+                        input.span(),
+                        format!("`{}` metaitem did not have value", key),
+                    )
+                })?;
+                name_val = Some(value);
+            } else {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!(
+                        "Unknown resource attribute `{}`, expected `name` or `timeouts`",
+                        key
+                    ),
+                ));
+            }
+
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        let name_val = name_val.ok_or_else(|| {
+            syn::Error::new(
+                input.span(),
+                "Expected `name` metaitem in resource attribute",
+            )
+        })?;
+
+        Ok(Self { name_val, timeouts })
+    }
+}
 
-        let name_pos = metaitem
+fn parse_timeouts(input: ParseStream) -> syn::Result<Vec<(Ident, Duration)>> {
+    let mut timeouts: Vec<(Ident, Duration)> = Vec::new();
+
+    while !input.is_empty() {
+        let operation = input.parse::<Ident>()?;
+        if !["create", "update", "delete"]
             .iter()
-            .by_ref()
-            .position(|i| *i == "name")
-            .ok_or_else(|| {
-                syn::Error::new(
-                    input.span(),
-                    "Expected `name` metaitem in resource attribute",
-                )
-            })?;
-
-        let name_val = metaitem
-            .into_iter()
-            .nth(name_pos + 1)
-            .ok_or_else(|| syn::Error::new(input.span(), "`name` metaitem did not have value"))?;
-
-        Ok(Self { name_val })
+            .any(|op| operation == op)
+        {
+            return Err(syn::Error::new(
+                operation.span(),
+                format!(
+                    "Unknown timeout `{}`, expected `create`, `update` or `delete`",
+                    operation
+                ),
+            ));
+        }
+        if timeouts.iter().any(|(op, _)| *op == operation) {
+            return Err(syn::Error::new(
+                operation.span(),
+                format!("Duplicate `{}` timeout", operation),
+            ));
+        }
+
+        input.parse::<Token![=]>()?;
+        let value = input.parse::<LitStr>()?;
+        let duration = parse_duration(&value.value()).ok_or_else(|| {
+            syn::Error::new(
+                value.span(),
+                format!(
+                    "Invalid duration `{}`, expected e.g. \"30s\", \"10m\" or \"1h30m\"",
+                    value.value()
+                ),
+            )
+        })?;
+        timeouts.push((operation, duration));
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+
+    Ok(timeouts)
+}
+
+/// Parses durations made of numbers with `ms`, `s`, `m` or `h` units, such as `1h30m`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut rest = value.trim();
+
+    if rest.is_empty() {
+        return None;
     }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount = rest[..digits].parse::<u64>().ok()?;
+        rest = &rest[digits..];
+
+        let units = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..units] {
+            "ms" => Duration::from_millis(1),
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(60 * 60),
+            _ => return None,
+        };
+        rest = &rest[units..];
+
+        total += unit.checked_mul(u32::try_from(amount).ok()?)?;
+    }
+
+    Some(total)
 }
 
 mod keyword {
@@ -89,7 +193,7 @@ mod test {
     #[test]
     fn test_resource_state_attribute_returns_error_if_name_is_not_found() {
         let input = quote! {
-            #[resource(foo = bar)]
+            #[resource(timeouts(create = "10m"))]
         };
 
         let err = parse2::<StateAttribute>(input).err().unwrap();
@@ -100,6 +204,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_resource_state_attribute_returns_error_for_unknown_keys() {
+        let input = quote! {
+            #[resource(name = foo, nmae = bar)]
+        };
+        let err = parse2::<StateAttribute>(input).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown resource attribute `nmae`, expected `name` or `timeouts`"
+        );
+
+        let input = quote! {
+            #[resource(name = foo, timeouts(craete = "10m"))]
+        };
+        let err = parse2::<StateAttribute>(input).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown timeout `craete`, expected `create`, `update` or `delete`"
+        );
+    }
+
     #[test]
     fn test_resource_state_attribute_returns_error_if_name_does_not_have_value() {
         let input = quote! {
@@ -110,4 +235,47 @@ mod test {
 
         assert_eq!(err.to_string(), "`name` metaitem did not have value");
     }
+
+    #[test]
+    fn test_resource_state_attribute_parses_timeouts() {
+        let input = quote! {
+            #[resource(name = foo, timeouts(create = "10m", delete = "1h30s"))]
+        };
+
+        let StateAttribute::Resource(resource_field) = parse2(input).unwrap();
+
+        let timeouts: Vec<_> = resource_field
+            .timeouts
+            .iter()
+            .map(|(op, duration)| (op.to_string(), *duration))
+            .collect();
+        assert_eq!(
+            timeouts,
+            vec![
+                (String::from("create"), Duration::from_secs(600)),
+                (String::from("delete"), Duration::from_secs(3630)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resource_state_attribute_returns_error_for_invalid_timeouts() {
+        let input = quote! {
+            #[resource(name = foo, timeouts(read = "1m"))]
+        };
+        let err = parse2::<StateAttribute>(input).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown timeout `read`, expected `create`, `update` or `delete`"
+        );
+
+        let input = quote! {
+            #[resource(name = foo, timeouts(create = "10 minutes"))]
+        };
+        let err = parse2::<StateAttribute>(input).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Invalid duration `10 minutes`, expected e.g. \"30s\", \"10m\" or \"1h30m\""
+        );
+    }
 }
@@ -1,48 +1,938 @@
 use std::collections::{HashMap, HashSet};
 
-use helpers::{get_item_attribute, provider_trait_name};
+use helpers::{
+    async_provider_trait_name, get_item_attribute, provider_trait_name, recorded_resource_name,
+    resource_trait_name,
+};
 use proc_macro2::Span;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::Ident;
 
 use crate::{items::item_state::ItemState, resource::Resource, state_attribute::StateAttribute};
 
 pub(crate) struct StateDefintion {
+    /// The resources in execution order, each after its dependencies.
     resources: Vec<Resource>,
+    /// Indices into `resources` in the order the resources are declared.
+    declared: Vec<usize>,
 }
 
 impl StateDefintion {
-    pub(crate) fn expand(self) -> proc_macro2::TokenStream {
+    /// Expands the State for the `state!` invocation whose tokens are `source`.
+    pub(crate) fn expand(self, source: &str) -> proc_macro2::TokenStream {
         let provider_trait_name = provider_trait_name();
-        let resource_instantiation = self.resources.iter().map(|r| r.expand_instantiation());
-        let resource_name = self.resources.iter().map(|r| r.expand_name());
+        let async_provider_trait_name = async_provider_trait_name();
+        let resource_trait_name = resource_trait_name();
+        let recorded_resource_name = recorded_resource_name();
+        let resource_instantiation = self
+            .resources
+            .iter()
+            .map(|r| r.expand_instantiation())
+            .collect::<Vec<proc_macro2::TokenStream>>();
+        let resource_name = self
+            .resources
+            .iter()
+            .map(|r| r.expand_name())
+            .collect::<Vec<proc_macro2::TokenStream>>();
+        let resource_label = self
+            .resources
+            .iter()
+            .map(|r| r.name())
+            .collect::<Vec<String>>();
+        let resource_type = self
+            .resources
+            .iter()
+            .map(|r| &r.item_resource.ident)
+            .collect::<Vec<&Ident>>();
+        let resource_index = (0..self.resources.len()).collect::<Vec<usize>>();
+        let resource_slot = self
+            .resources
+            .iter()
+            .map(|r| format_ident!("__{}", r.name_val))
+            .collect::<Vec<Ident>>();
+        let slot_type = resource_type.clone();
+        let dependency_name = self
+            .resources
+            .iter()
+            .map(|r| {
+                self.dependency_indices(r)
+                    .into_iter()
+                    .map(|i| &self.resources[i].name_val)
+            })
+            .map(|deps| deps.collect::<Vec<&Ident>>())
+            .collect::<Vec<Vec<&Ident>>>();
+        let dependency_label = dependency_name
+            .iter()
+            .map(|deps| deps.iter().map(|dep| dep.to_string()).collect())
+            .collect::<Vec<Vec<String>>>();
+        let dependency_slot = dependency_name
+            .iter()
+            .map(|deps| deps.iter().map(|dep| format_ident!("__{}", dep)).collect())
+            .collect::<Vec<Vec<Ident>>>();
+        let dependency_index = self
+            .resources
+            .iter()
+            .map(|r| self.dependency_indices(r))
+            .collect::<Vec<Vec<usize>>>();
+        let declared_label = self
+            .declared
+            .iter()
+            .map(|&i| &resource_label[i])
+            .collect::<Vec<&String>>();
+        let declared_type = self
+            .declared
+            .iter()
+            .map(|&i| resource_type[i])
+            .collect::<Vec<&Ident>>();
+        let declared_dependency_label = self
+            .declared
+            .iter()
+            .map(|&i| &dependency_label[i])
+            .collect::<Vec<&Vec<String>>>();
+        let input_assertion = self.resources.iter().map(|r| r.expand_input_assertion());
+        let resource_timeouts = self
+            .resources
+            .iter()
+            .map(|r| r.expand_timeouts(false))
+            .collect::<Vec<proc_macro2::TokenStream>>();
+        let resource_timeouts_by_ref = self.resources.iter().map(|r| r.expand_timeouts(true));
 
         quote! {
+            #(#input_assertion)*
+
+            /// The names of the resources, in the order they are declared.
+            pub const RESOURCE_NAMES: &[&str] = &[#(#declared_label),*];
+
+            /// The name and type of every resource, in the order they are declared.
+            pub const RESOURCE_TYPES: &[(&str, &str)] = &[
+                #((#declared_label, <#declared_type as ::runtime::Attributes>::TYPE_NAME)),*
+            ];
+
+            /// The name of every resource with the names of the resources it depends on, in the
+            /// order they are declared.
+            pub const DEPENDENCIES: &[(&str, &[&str])] = &[
+                #((#declared_label, &[#(#declared_dependency_label),*])),*
+            ];
+
+            /// The names of the resources in the order they run one at a time, each after its
+            /// dependencies. With parallelism, independent resources may overlap.
+            pub const EXECUTION_ORDER: &[&str] = &[#(#resource_label),*];
+
             pub struct State<P> {
-                provider: P
+                provider: P,
+                parallelism: usize,
+                cancellation: ::runtime::CancellationToken,
             }
 
-            impl<P: #provider_trait_name> State<P> {
+            impl<P> State<P> {
 
-                pub fn new(provider: P) -> Self {
+                /// Wraps `provider` as it is, skipping the validation `configure` does, for
+                /// providers built in code such as mocks and test wrappers.
+                pub fn from_configured(provider: P) -> Self {
                     Self {
-                        provider
+                        provider,
+                        parallelism: ::runtime::DEFAULT_PARALLELISM,
+                        cancellation: ::runtime::CancellationToken::new(),
                     }
                 }
 
-                #[allow(unused_variables)]
-                pub fn plan(&self) {
+                /// Limits how many provider operations apply and destroy run at once.
+                pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+                    self.parallelism = parallelism.max(1);
+                    self
+                }
+
+                /// Lets apply and destroy be interrupted by cancelling `cancellation` or a clone
+                /// of it. No new operations are started after that, operations in progress get
+                /// until the end of its grace period, and everything done is recorded in the state.
+                pub fn with_cancellation(mut self, cancellation: ::runtime::CancellationToken) -> Self {
+                    self.cancellation = cancellation;
+                    self
+                }
+
+                pub fn provider(&self) -> &P {
+                    &self.provider
+                }
+
+                /// Compares every resource against its record in `state`, without calling the
+                /// provider, and plans the delete of every resource recorded by this provider that
+                /// is no longer defined. The plan's `Display` renders it for review, and it can be saved to
+                /// apply later with `apply_plan`.
+                pub fn plan(&self, state: &::runtime::StateFile) -> Result<::runtime::Plan, ::runtime::StateError>
+                where
+                    P: #provider_trait_name,
+                {
+                    let mut plan = ::runtime::Plan::new();
+
                     #(
                         #resource_instantiation
-                        let res = self.provider.get(&#resource_name);
+                        let action = Self::restore_resource(state, #resource_label, &mut #resource_name)?;
+                        plan.push(::runtime::PlannedChange::for_resource(
+                            #resource_label,
+                            &#resource_name,
+                            state.get(#resource_label),
+                            action,
+                        )?.with_dependencies(&[#(#dependency_label),*]));
                     )*
+                    for (record, resource) in Self::removed_resources(state, P::NAME)? {
+                        plan.push(resource.planned_delete(&record)?);
+                    }
+
+                    let fingerprints = ::runtime::Fingerprints::new(
+                        state,
+                        #source,
+                        ::runtime::ProviderInfo::new(P::NAME, P::VERSION),
+                        &plan.changes,
+                    )?;
+                    Ok(plan.with_fingerprints(fingerprints))
                 }
 
+                /// The resources recorded by `provider` that are no longer in the definition, most
+                /// recently recorded first, restored so that they can be deleted. Records of other
+                /// providers, and of types the provider's module does not have, are left alone.
+                fn removed_resources(
+                    state: &::runtime::StateFile,
+                    provider: &str,
+                ) -> Result<Vec<(::runtime::ResourceRecord, #recorded_resource_name)>, ::runtime::StateError> {
+                    state
+                        .resources()
+                        .iter()
+                        .rev()
+                        .filter(|record| Self::is_undefined(record, provider))
+                        .filter(|record| #recorded_resource_name::has_type(&record.resource_type))
+                        .map(|record| Ok((record.clone(), #recorded_resource_name::from_record(record)?)))
+                        .collect()
+                }
+
+                /// Whether `record` was made by `provider` for a resource that is not defined.
+                fn is_undefined(record: &::runtime::ResourceRecord, provider: &str) -> bool {
+                    record.provider.name == provider && !RESOURCE_NAMES.contains(&record.name.as_str())
+                }
+
+                /// Warns about every resource recorded by an incompatible version of `provider`,
+                /// and about every undefined one of a type its module does not have, which is not
+                /// deleted.
+                fn check_records(
+                    state: &::runtime::StateFile,
+                    provider: &str,
+                    version: &str,
+                ) -> Vec<::runtime::Diagnostic> {
+                    let unknown = state
+                        .resources()
+                        .iter()
+                        .filter(|record| Self::is_undefined(record, provider))
+                        .filter(|record| !#recorded_resource_name::has_type(&record.resource_type))
+                        .map(|record| {
+                            ::runtime::Diagnostic::warning(
+                                Some(&record.name),
+                                format!(
+                                    "recorded as type `{}`, which provider `{}` does not have, so it is not deleted",
+                                    record.resource_type, provider
+                                ),
+                            )
+                        });
+
+                    state.check_provider(provider, version).into_iter().chain(unknown).collect()
+                }
+
+                /// The name and type of every resource by index, with the removed resources
+                /// after the ones the scheduler runs.
+                fn resources_with(
+                    removed: &[(::runtime::ResourceRecord, #recorded_resource_name)],
+                ) -> Vec<(&str, &str)> {
+                    Self::resources()
+                        .into_iter()
+                        .chain(removed.iter().map(|(record, _)| (record.name.as_str(), record.resource_type.as_str())))
+                        .collect()
+                }
+
+                /// The name and type of every resource, in the order they are declared to the
+                /// scheduler.
+                fn resources() -> Vec<(&'static str, &'static str)> {
+                    vec![#((#resource_label, <#resource_type as ::runtime::Attributes>::TYPE_NAME)),*]
+                }
+
+                /// The resources and the dependencies between them, in the order they are declared
+                /// to the scheduler.
+                fn graph() -> ::runtime::Graph {
+                    ::runtime::Graph::new(vec![#(vec![#(#dependency_index),*]),*])
+                }
+
+                /// The names of the resources, in the order they are declared.
+                pub fn resource_names() -> &'static [&'static str] {
+                    RESOURCE_NAMES
+                }
+
+                pub fn resource_type(name: &str) -> Option<&'static str> {
+                    RESOURCE_TYPES
+                        .iter()
+                        .find(|(resource, _)| *resource == name)
+                        .map(|(_, resource_type)| *resource_type)
+                }
+
+                /// The names of the resources that `name` depends on, if it is a resource.
+                pub fn dependencies(name: &str) -> Option<&'static [&'static str]> {
+                    DEPENDENCIES
+                        .iter()
+                        .find(|(resource, _)| *resource == name)
+                        .map(|(_, dependencies)| *dependencies)
+                }
+
+                /// The names of the resources in the order they run one at a time, each after its
+                /// dependencies.
+                pub fn execution_order() -> &'static [&'static str] {
+                    EXECUTION_ORDER
+                }
+
+                /// The resources and the dependencies between them, which renders as a Graphviz
+                /// DOT or Mermaid diagram, colored by planned action after `with_plan`.
+                pub fn resource_graph() -> ::runtime::ResourceGraph {
+                    ::runtime::ResourceGraph::new(&Self::resources(), &Self::graph())
+                }
+
+                /// Restores the recorded outputs of `resource`, so that dependent resources see
+                /// them, and returns the action needed to bring it up to date.
+                fn restore_resource<R: #resource_trait_name>(
+                    state: &::runtime::StateFile,
+                    name: &str,
+                    resource: &mut R,
+                ) -> Result<::runtime::Action, ::runtime::StateError> {
+                    const {
+                        assert!(
+                            R::HAS_VALUES,
+                            "planning, applying or destroying a State needs provider_macro's `serde` feature",
+                        );
+                    }
+                    let record = state.get(name);
+                    let action = ::runtime::Action::for_resource(record, &resource.inputs()?, R::FORCE_NEW_FIELDS);
+
+                    if let Some(record) = record {
+                        resource.set_output_values(&record.outputs)?;
+                    }
+
+                    Ok(action)
+                }
+
+                /// Whether `action` updates a resource recorded as uncertain, which may not exist
+                /// if the operation that timed out was its create.
+                fn is_uncertain(
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    name: &str,
+                    action: ::runtime::Action,
+                ) -> bool {
+                    action == ::runtime::Action::Update
+                        && Self::lock(state)
+                            .get(name)
+                            .is_some_and(|record| record.status == ::runtime::ResourceStatus::Uncertain)
+                }
+
+                /// The action for an uncertain resource after reading it with `result`: it is
+                /// created if the provider does not have it, and updated otherwise.
+                fn action_after_read(
+                    ctx: &::runtime::Context,
+                    result: Result<(), ::runtime::ProviderError>,
+                    attempts: u32,
+                ) -> Result<::runtime::Action, ::runtime::ApplyError> {
+                    match result {
+                        Ok(()) => Ok(::runtime::Action::Update),
+                        Err(err) if err.is_not_found() => Ok(::runtime::Action::Create),
+                        Err(err) => Err(::runtime::ApplyError::provider(&ctx.resource, ctx.operation, err, attempts)),
+                    }
+                }
+
+                /// The provider operation that brings a resource up to date for `action`.
+                fn operation(action: ::runtime::Action) -> ::runtime::Operation {
+                    match action {
+                        ::runtime::Action::Create | ::runtime::Action::Replace => ::runtime::Operation::Create,
+                        ::runtime::Action::Update => ::runtime::Operation::Update,
+                        _ => ::runtime::Operation::Read,
+                    }
+                }
+
+                /// A context for `operation` on the resource named `name`, due by its timeout.
+                fn context(
+                    &self,
+                    name: &str,
+                    resource_type: &'static str,
+                    operation: ::runtime::Operation,
+                    timeouts: &::runtime::Timeouts,
+                ) -> ::runtime::Context {
+                    let ctx = ::runtime::Context::new(name, resource_type, operation)
+                        .with_cancellation(self.cancellation.clone());
+
+                    match timeouts.get(operation) {
+                        Some(timeout) => ctx.with_deadline(::std::time::Instant::now() + timeout),
+                        None => ctx,
+                    }
+                }
+
+                /// Records `resource` in `state` after the operation in `ctx` finished with
+                /// `result`, as uncertain if it timed out.
+                #[allow(clippy::too_many_arguments)]
+                fn record_applied<R: #resource_trait_name>(
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                    provider: ::runtime::ProviderInfo,
+                    resource: &R,
+                    action: ::runtime::Action,
+                    ctx: &::runtime::Context,
+                    result: Result<(), ::runtime::ProviderError>,
+                    attempts: u32,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    let name = ctx.resource.as_str();
+                    let timed_out = match result {
+                        Ok(()) => None,
+                        Err(err) if err.is_interrupted() => {
+                            return Err(::runtime::ApplyError::provider(name, ctx.operation, err, attempts))
+                        }
+                        Err(err) if err.is_timeout() || ctx.is_expired() => Some(err),
+                        Err(err) => {
+                            return Err(::runtime::ApplyError::provider(name, ctx.operation, err, attempts))
+                        }
+                    };
+
+                    let status = match timed_out {
+                        Some(_) => ::runtime::ResourceStatus::Uncertain,
+                        None => ::runtime::ResourceStatus::Ready,
+                    };
+                    Self::lock(state).record(
+                        ::runtime::ResourceRecord::new(name, R::TYPE_NAME, provider)
+                            .with_values(resource.inputs()?, resource.outputs()?)
+                            .with_status(status),
+                    );
+
+                    if let Some(err) = timed_out {
+                        return Err(::runtime::ApplyError::timeout(name, ctx.operation, err));
+                    }
+                    Self::lock(report).push(name, R::TYPE_NAME, action, attempts);
+
+                    Ok(())
+                }
+
+                /// Sets the recorded inputs of `resource`, to delete the resource it replaces, and
+                /// returns the inputs it had.
+                fn restore_inputs<R: #resource_trait_name>(
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    name: &str,
+                    resource: &mut R,
+                ) -> Result<::runtime::Values, ::runtime::ApplyError> {
+                    let inputs = resource.inputs()?;
+                    if let Some(record) = Self::lock(state).get(name) {
+                        resource.set_input_values(&record.inputs)?;
+                    }
+
+                    Ok(inputs)
+                }
+
+                /// Removes the resource in `ctx` from `state` after it was deleted, or if the
+                /// provider no longer has it, or marks it uncertain if the delete timed out.
+                fn record_deleted(
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    ctx: &::runtime::Context,
+                    result: Result<(), ::runtime::ProviderError>,
+                    attempts: u32,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    let name = ctx.resource.as_str();
+                    match result {
+                        Ok(()) => {}
+                        Err(err) if err.is_not_found() => {}
+                        Err(err) if err.is_interrupted() => {
+                            return Err(::runtime::ApplyError::provider(name, ctx.operation, err, attempts))
+                        }
+                        Err(err) if err.is_timeout() || ctx.is_expired() => {
+                            Self::lock(state).mark_uncertain(name);
+                            return Err(::runtime::ApplyError::timeout(name, ctx.operation, err));
+                        }
+                        Err(err) => {
+                            return Err(::runtime::ApplyError::provider(name, ctx.operation, err, attempts))
+                        }
+                    }
+
+                    Self::lock(state).remove(name);
+
+                    Ok(())
+                }
+
+                fn lock<T>(mutex: &::std::sync::Mutex<T>) -> ::std::sync::MutexGuard<'_, T> {
+                    mutex.lock().unwrap_or_else(|err| err.into_inner())
+                }
+            }
+
+            impl<P: #provider_trait_name> State<P> {
+
+                /// Configures the provider from `config`, which fails if the config is invalid,
+                /// before any resource operation can run.
+                pub fn configure(config: P::Config) -> Result<Self, ::runtime::ConfigError> {
+                    P::configure(config).map(Self::from_configured)
+                }
+
+                /// Applies a plan saved earlier, refusing to if `state`, this definition or the
+                /// provider version changed since it was made. Each resource gets the action the
+                /// plan has for it, and fails instead if it now needs another, e.g. because a
+                /// dependency it reads was replaced, so that exactly the reviewed changes are made.
+                pub fn apply_plan(&self, plan: &::runtime::Plan, state: &mut ::runtime::StateFile) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    plan.verify(&self.plan(state)?)?;
+                    self.apply_changes(state, Some(plan))
+                }
+
+                /// Creates, updates or refreshes every resource and records it, along with the
+                /// provider name and version, in `state`. Each resource starts as soon as its
+                /// dependencies are done, with up to the configured parallelism running at once.
+                /// The report warns about each resource recorded by an incompatible provider
+                /// version. A resource whose operation times out is recorded as uncertain. Resources
+                /// recorded by this provider that are no longer defined are deleted afterwards.
+                ///
+                /// A failed resource does not stop the others: its dependents are skipped, every
+                /// other resource is still applied and recorded, and the incomplete error holds a
+                /// report of what succeeded, failed and was skipped. If the run is cancelled, the
+                /// interrupted error holds that report once the operations in progress are done,
+                /// or have outlasted the grace period and are recorded as uncertain.
+                pub fn apply(&self, state: &mut ::runtime::StateFile) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    self.apply_changes(state, None)
+                }
+
+                /// Applies every resource as `apply` does, with the action `plan` has for it if
+                /// there is one.
+                fn apply_changes(
+                    &self,
+                    state: &mut ::runtime::StateFile,
+                    plan: Option<&::runtime::Plan>,
+                ) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    let report = ::std::sync::Mutex::new(
+                        ::runtime::ApplyReport::new(Self::check_records(state, P::NAME, P::VERSION)),
+                    );
+                    let removed = Self::removed_resources(state, P::NAME)?;
+                    if let Some(plan) = plan {
+                        for (record, _) in &removed {
+                            plan.check_action(&record.name, ::runtime::Action::Delete)?;
+                        }
+                    }
+                    let state = ::std::sync::Mutex::new(state);
+                    #(let #resource_slot = ::std::sync::OnceLock::<#slot_type>::new();)*
+
+                    let mut outcomes = Self::graph().run(self.parallelism, &self.cancellation, |index| -> Result<(), ::runtime::ApplyError> { match index {
+                        #(
+                            #resource_index => {
+                                #(
+                                    let #dependency_name = #dependency_slot
+                                        .get()
+                                        .expect("dependencies are applied first");
+                                )*
+                                #resource_instantiation
+                                let timeouts = #resource_timeouts;
+                                self.apply_resource(&state, plan, #resource_label, &mut #resource_name, &timeouts, &report)?;
+                                let _ = #resource_slot.set(#resource_name);
+                                Ok(())
+                            }
+                        )*
+                        _ => unreachable!(),
+                    }});
+                    outcomes.extend(self.delete_removed(&state, &removed, &report));
+
+                    report
+                        .into_inner()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .finish(outcomes, &Self::resources_with(&removed), self.cancellation.is_cancelled())
+                }
+
+                /// Deletes every recorded resource once its dependents are deleted, with up to the
+                /// configured parallelism running at once, and removes it from `state`. A resource
+                /// whose delete fails keeps the resources it depends on. Resources recorded by this
+                /// provider that are no longer defined are deleted first.
+                pub fn destroy(&self, state: &mut ::runtime::StateFile) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    let report = ::std::sync::Mutex::new(
+                        ::runtime::ApplyReport::new(Self::check_records(state, P::NAME, P::VERSION)),
+                    );
+
+                    #(
+                        #resource_instantiation
+                        Self::restore_resource(state, #resource_label, &mut #resource_name)?;
+                    )*
+                    let removed = Self::removed_resources(state, P::NAME)?;
+
+                    let state = ::std::sync::Mutex::new(state);
+                    let removed_outcomes = self.delete_removed(&state, &removed, &report);
+                    let mut outcomes = Self::graph().reversed().run(self.parallelism, &self.cancellation, |index| -> Result<(), ::runtime::ApplyError> { match index {
+                        #(
+                            #resource_index => {
+                                let timeouts = #resource_timeouts;
+                                self.destroy_resource(&state, #resource_label, &#resource_name, &timeouts, &report)
+                            }
+                        )*
+                        _ => unreachable!(),
+                    }});
+                    outcomes.extend(removed_outcomes);
+
+                    report
+                        .into_inner()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .finish(outcomes, &Self::resources_with(&removed), self.cancellation.is_cancelled())
+                }
+
+                fn apply_resource<R: #resource_trait_name>(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    plan: Option<&::runtime::Plan>,
+                    name: &str,
+                    resource: &mut R,
+                    timeouts: &::runtime::Timeouts,
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    let mut action = Self::restore_resource(&Self::lock(state), name, resource)?;
+                    if let Some(plan) = plan {
+                        plan.check_action(name, action)?;
+                    }
+                    if Self::is_uncertain(state, name, action) {
+                        let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Read, timeouts);
+                        let (result, attempts) = self.call_with_retries(&ctx, resource);
+                        action = Self::action_after_read(&ctx, result, attempts)?;
+                    }
+                    if action == ::runtime::Action::Replace {
+                        let inputs = Self::restore_inputs(state, name, resource)?;
+                        let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Delete, timeouts);
+                        let (result, attempts) = self.delete_with_retries(&ctx, resource);
+                        resource.set_input_values(&inputs)?;
+                        Self::record_deleted(state, &ctx, result, attempts)?;
+                    }
+
+                    let ctx = self.context(name, R::TYPE_NAME, Self::operation(action), timeouts);
+                    let (result, attempts) = self.call_with_retries(&ctx, resource);
+
+                    let provider = ::runtime::ProviderInfo::new(P::NAME, P::VERSION);
+                    Self::record_applied(state, report, provider, resource, action, &ctx, result, attempts)
+                }
+
+                fn destroy_resource<R: #resource_trait_name>(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    name: &str,
+                    resource: &R,
+                    timeouts: &::runtime::Timeouts,
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    if Self::lock(state).get(name).is_none() {
+                        return Ok(());
+                    }
+
+                    let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Delete, timeouts);
+                    let (result, attempts) = self.delete_with_retries(&ctx, resource);
+                    Self::record_deleted(state, &ctx, result, attempts)?;
+                    Self::lock(report).push(name, R::TYPE_NAME, ::runtime::Action::Delete, attempts);
+
+                    Ok(())
+                }
+
+                /// Deletes the resources that are no longer in the definition one at a time, until
+                /// the run is cancelled, and returns the outcome of each.
+                fn delete_removed(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    removed: &[(::runtime::ResourceRecord, #recorded_resource_name)],
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Vec<::runtime::Outcome<::runtime::ApplyError>>
+                where
+                    P: Sync,
+                {
+                    let mut outcomes = Vec::new();
+
+                    for (record, resource) in removed {
+                        if self.cancellation.is_cancelled() {
+                            outcomes.push(::runtime::Outcome::Cancelled);
+                            continue;
+                        }
+
+                        let ctx = self.context(&record.name, resource.type_name(), ::runtime::Operation::Delete, &resource.timeouts());
+                        let (result, attempts) = resource
+                            .retry_policy()
+                            .unwrap_or_else(|| self.provider.retry_policy())
+                            .run(&ctx, || resource.delete(&self.provider, &ctx));
+
+                        outcomes.push(match Self::record_deleted(state, &ctx, result, attempts) {
+                            Ok(()) => {
+                                Self::lock(report).push(&record.name, resource.type_name(), ::runtime::Action::Delete, attempts);
+                                ::runtime::Outcome::Succeeded
+                            }
+                            Err(err) => ::runtime::Outcome::Failed(err),
+                        });
+                    }
+
+                    outcomes
+                }
+
+                /// Creates, updates or reads `resource` as `ctx` says, retrying as its policy
+                /// allows.
+                fn call_with_retries<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> (Result<(), ::runtime::ProviderError>, u32) {
+                    self.retry_policy(resource).run(ctx, || match ctx.operation {
+                        ::runtime::Operation::Create => self.provider.create(ctx, resource),
+                        ::runtime::Operation::Update => self.provider.update(ctx, resource),
+                        _ => self.provider.read(ctx, resource),
+                    })
+                }
+
+                fn delete_with_retries<R: #resource_trait_name>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &R,
+                ) -> (Result<(), ::runtime::ProviderError>, u32) {
+                    self.retry_policy(resource).run(ctx, || self.provider.delete(ctx, resource))
+                }
+
+                /// The resource's own retry policy, or else the provider's.
+                fn retry_policy<R: #resource_trait_name>(&self, resource: &R) -> ::runtime::RetryPolicy {
+                    resource
+                        .retry_policy()
+                        .unwrap_or_else(|| self.provider.retry_policy())
+                }
+            }
+
+            impl<P: #async_provider_trait_name> State<P> {
+
+                /// Like `apply`, but awaits the provider's async operations. Resources run as
+                /// futures within the calling task, overlapping up to the configured parallelism,
+                /// so no thread is started per resource.
+                pub async fn apply_async(&self, state: &mut ::runtime::StateFile) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    let report = ::std::sync::Mutex::new(
+                        ::runtime::ApplyReport::new(Self::check_records(state, P::NAME, P::VERSION)),
+                    );
+                    let removed = Self::removed_resources(state, P::NAME)?;
+                    let state = ::std::sync::Mutex::new(state);
+                    #(let #resource_slot = ::std::sync::OnceLock::<#slot_type>::new();)*
+
+                    let mut outcomes = Self::graph().run_async(self.parallelism, &self.cancellation, |index| {
+                        let (state, report) = (&state, &report);
+                        #(let #resource_slot = &#resource_slot;)*
+
+                        async move {
+                            match index {
+                                #(
+                                    #resource_index => {
+                                        #(
+                                            let #dependency_name = #dependency_slot
+                                                .get()
+                                                .expect("dependencies are applied first");
+                                        )*
+                                        #resource_instantiation
+                                        let timeouts = #resource_timeouts;
+                                        self.apply_resource_async(state, #resource_label, &mut #resource_name, &timeouts, report).await?;
+                                        let _ = #resource_slot.set(#resource_name);
+                                        Ok::<(), ::runtime::ApplyError>(())
+                                    }
+                                )*
+                                _ => unreachable!(),
+                            }
+                        }
+                    }).await;
+                    outcomes.extend(self.delete_removed_async(&state, &removed, &report).await);
+
+                    report
+                        .into_inner()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .finish(outcomes, &Self::resources_with(&removed), self.cancellation.is_cancelled())
+                }
+
+                /// Like `destroy`, but awaits the provider's async operations.
+                pub async fn destroy_async(&self, state: &mut ::runtime::StateFile) -> Result<::runtime::ApplyReport, ::runtime::ApplyError>
+                where
+                    P: Sync,
+                {
+                    let report = ::std::sync::Mutex::new(
+                        ::runtime::ApplyReport::new(Self::check_records(state, P::NAME, P::VERSION)),
+                    );
+
+                    #(
+                        #resource_instantiation
+                        Self::restore_resource(state, #resource_label, &mut #resource_name)?;
+                    )*
+                    let removed = Self::removed_resources(state, P::NAME)?;
+
+                    let state = ::std::sync::Mutex::new(state);
+                    let removed_outcomes = self.delete_removed_async(&state, &removed, &report).await;
+                    let mut outcomes = Self::graph().reversed().run_async(self.parallelism, &self.cancellation, |index| {
+                        let (state, report) = (&state, &report);
+                        #(let #resource_name = &#resource_name;)*
+
+                        async move {
+                            match index {
+                                #(
+                                    #resource_index => {
+                                        let timeouts = #resource_timeouts_by_ref;
+                                        self.destroy_resource_async(state, #resource_label, #resource_name, &timeouts, report).await
+                                    }
+                                )*
+                                _ => unreachable!(),
+                            }
+                        }
+                    }).await;
+                    outcomes.extend(removed_outcomes);
+
+                    report
+                        .into_inner()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .finish(outcomes, &Self::resources_with(&removed), self.cancellation.is_cancelled())
+                }
+
+                async fn apply_resource_async<R: #resource_trait_name + Send + Sync>(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    name: &str,
+                    resource: &mut R,
+                    timeouts: &::runtime::Timeouts,
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    let mut action = Self::restore_resource(&Self::lock(state), name, resource)?;
+                    if Self::is_uncertain(state, name, action) {
+                        let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Read, timeouts);
+                        let (result, attempts) = self.call_with_retries_async(&ctx, resource).await;
+                        action = Self::action_after_read(&ctx, result, attempts)?;
+                    }
+                    if action == ::runtime::Action::Replace {
+                        let inputs = Self::restore_inputs(state, name, resource)?;
+                        let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Delete, timeouts);
+                        let (result, attempts) = self.delete_with_retries_async(&ctx, resource).await;
+                        resource.set_input_values(&inputs)?;
+                        Self::record_deleted(state, &ctx, result, attempts)?;
+                    }
+
+                    let ctx = self.context(name, R::TYPE_NAME, Self::operation(action), timeouts);
+                    let (result, attempts) = self.call_with_retries_async(&ctx, resource).await;
+
+                    let provider = ::runtime::ProviderInfo::new(P::NAME, P::VERSION);
+                    Self::record_applied(state, report, provider, resource, action, &ctx, result, attempts)
+                }
+
+                async fn destroy_resource_async<R: #resource_trait_name + Sync>(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    name: &str,
+                    resource: &R,
+                    timeouts: &::runtime::Timeouts,
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Result<(), ::runtime::ApplyError> {
+                    if Self::lock(state).get(name).is_none() {
+                        return Ok(());
+                    }
+
+                    let ctx = self.context(name, R::TYPE_NAME, ::runtime::Operation::Delete, timeouts);
+                    let (result, attempts) = self.delete_with_retries_async(&ctx, resource).await;
+                    Self::record_deleted(state, &ctx, result, attempts)?;
+                    Self::lock(report).push(name, R::TYPE_NAME, ::runtime::Action::Delete, attempts);
+
+                    Ok(())
+                }
+
+                /// Like `delete_removed`, but awaits the provider's async deletes.
+                async fn delete_removed_async(
+                    &self,
+                    state: &::std::sync::Mutex<&mut ::runtime::StateFile>,
+                    removed: &[(::runtime::ResourceRecord, #recorded_resource_name)],
+                    report: &::std::sync::Mutex<::runtime::ApplyReport>,
+                ) -> Vec<::runtime::Outcome<::runtime::ApplyError>>
+                where
+                    P: Sync,
+                {
+                    let mut outcomes = Vec::new();
+
+                    for (record, resource) in removed {
+                        if self.cancellation.is_cancelled() {
+                            outcomes.push(::runtime::Outcome::Cancelled);
+                            continue;
+                        }
+
+                        let ctx = self.context(&record.name, resource.type_name(), ::runtime::Operation::Delete, &resource.timeouts());
+                        let (result, attempts) = resource
+                            .retry_policy()
+                            .unwrap_or_else(|| self.provider.retry_policy())
+                            .run_async(&ctx, || resource.delete_async(&self.provider, &ctx))
+                            .await;
+
+                        outcomes.push(match Self::record_deleted(state, &ctx, result, attempts) {
+                            Ok(()) => {
+                                Self::lock(report).push(&record.name, resource.type_name(), ::runtime::Action::Delete, attempts);
+                                ::runtime::Outcome::Succeeded
+                            }
+                            Err(err) => ::runtime::Outcome::Failed(err),
+                        });
+                    }
+
+                    outcomes
+                }
+
+                /// Creates, updates or reads `resource` as `ctx` says, retrying as its policy
+                /// allows. An attempt still pending at the deadline is dropped.
+                async fn call_with_retries_async<R: #resource_trait_name + Send + Sync>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &mut R,
+                ) -> (Result<(), ::runtime::ProviderError>, u32) {
+                    let policy = self.async_retry_policy(resource);
+                    let mut attempts = policy.attempts(ctx);
+
+                    loop {
+                        let attempt = async {
+                            match ctx.operation {
+                                ::runtime::Operation::Create => self.provider.create(ctx, resource).await,
+                                ::runtime::Operation::Update => self.provider.update(ctx, resource).await,
+                                _ => self.provider.read(ctx, resource).await,
+                            }
+                        };
+                        if let Some(done) = attempts.run(attempt).await {
+                            return done;
+                        }
+                    }
+                }
+
+                async fn delete_with_retries_async<R: #resource_trait_name + Sync>(
+                    &self,
+                    ctx: &::runtime::Context,
+                    resource: &R,
+                ) -> (Result<(), ::runtime::ProviderError>, u32) {
+                    self.async_retry_policy(resource)
+                        .run_async(ctx, || self.provider.delete(ctx, resource))
+                        .await
+                }
+
+                /// The resource's own retry policy, or else the provider's.
+                fn async_retry_policy<R: #resource_trait_name>(&self, resource: &R) -> ::runtime::RetryPolicy {
+                    resource
+                        .retry_policy()
+                        .unwrap_or_else(|| self.provider.retry_policy())
+                }
             }
 
         }
     }
 
+    /// Indices of the resources `resource` depends on, each listed once.
+    fn dependency_indices(&self, resource: &Resource) -> Vec<usize> {
+        let mut indices = resource
+            .get_dependencies()
+            .iter()
+            .filter_map(|dep| self.resources.iter().position(|r| r.name_val == *dep))
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
     fn get_resources(item_state: ItemState) -> Result<Vec<Resource>, syn::Error> {
         let resources = item_state
             .item_resources()
@@ -59,6 +949,18 @@ impl StateDefintion {
             })
             .collect::<Result<Vec<Resource>, syn::Error>>()?;
 
+        let names = resources
+            .iter()
+            .map(|r| r.name_val.clone())
+            .collect::<Vec<Ident>>();
+        let resources = resources
+            .into_iter()
+            .map(|mut r| {
+                r.references = r.item_resource.get_references(&names);
+                r
+            })
+            .collect();
+
         Ok(resources)
     }
 
@@ -77,8 +979,8 @@ impl StateDefintion {
 
         let mut graph: HashMap<String, Vec<Ident>> = HashMap::new();
 
-        for (item, deps) in dependents {
-            graph.insert(item, deps);
+        for (item, deps) in &dependents {
+            graph.insert(item.clone(), deps.clone());
         }
 
         let mut results: Vec<Resource> = Vec::new();
@@ -135,7 +1037,11 @@ impl StateDefintion {
             Ok(())
         }
 
-        for dep in independents.iter().chain(graph.keys()) {
+        // Dependents are visited in declaration order, so that the execution order is stable.
+        for dep in independents
+            .iter()
+            .chain(dependents.iter().map(|(item, _)| item))
+        {
             dfs(
                 dep.to_string(),
                 &graph,
@@ -155,9 +1061,17 @@ impl TryFrom<ItemState> for StateDefintion {
 
     fn try_from(value: ItemState) -> Result<Self, Self::Error> {
         let resources = Self::get_resources(value)?;
+        let names = resources.iter().map(|r| r.name()).collect::<Vec<String>>();
         let resources = Self::resolve_dependencies(resources)?;
+        let declared = names
+            .iter()
+            .filter_map(|name| resources.iter().position(|r| r.name() == *name))
+            .collect();
 
-        Ok(Self { resources })
+        Ok(Self {
+            resources,
+            declared,
+        })
     }
 }
 